@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use settings::AutoSaveSettings;
+
+/// How often the inactivity delay is checked while edits are pending.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// When dirty buffers are written back to their files without an explicit
+/// save. Untitled buffers and read-only files are never auto-saved.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AutoSave {
+    /// Save once no edit happened for this long.
+    pub delay: Option<Duration>,
+    /// Save when the window loses focus.
+    pub on_focus_loss: bool,
+    /// Save the buffer being left when switching tabs or panes.
+    pub on_switch: bool,
+}
+
+impl AutoSave {
+    pub fn off() -> Self {
+        Self::default()
+    }
+
+    pub fn after(delay: Duration) -> Self {
+        Self {
+            delay: Some(delay),
+            ..Self::default()
+        }
+    }
+
+    pub fn on_focus_loss(mut self) -> Self {
+        self.on_focus_loss = true;
+        self
+    }
+
+    pub fn on_switch(mut self) -> Self {
+        self.on_switch = true;
+        self
+    }
+
+    /// Whether the inactivity delay ran out since the last edit.
+    pub fn is_due(&self, last_edit: Instant) -> bool {
+        self.delay.is_some_and(|delay| last_edit.elapsed() >= delay)
+    }
+}
+
+impl From<&AutoSaveSettings> for AutoSave {
+    fn from(settings: &AutoSaveSettings) -> Self {
+        Self {
+            delay: (settings.delay_ms > 0)
+                .then_some(Duration::from_millis(settings.delay_ms)),
+            on_focus_loss: settings.on_focus_loss,
+            on_switch: settings.on_switch,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, container, row, scrollable, text, text_input, Column},
+    Element, Length, Task,
+};
+use registers::{clipboard, Dock, Event, Register};
+use ui::labeled_button;
+
+/// Characters of an entry shown in the list.
+const PREVIEW_LEN: usize = 80;
+
+fn input_id() -> text_input::Id {
+    text_input::Id::new("clipboard")
+}
+
+/// Pastes an older copy from the clipboard history, or copies to and
+/// pastes from the named registers.
+pub struct ClipboardPanel {
+    visible: bool,
+    input: String,
+}
+
+impl ClipboardPanel {
+    pub fn new() -> Self {
+        clipboard::set_persisted(settings::current().clipboard.persist);
+        Self {
+            visible: false,
+            input: String::new(),
+        }
+    }
+
+    /// The register named in the input, if it names one.
+    fn register(&self) -> Option<char> {
+        let mut chars = self.input.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(name), None) if clipboard::is_register(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// The first line of `text`, shortened, and how many lines follow.
+fn preview(text: &str) -> String {
+    let first = text.lines().next().unwrap_or_default();
+    let mut shown: String = first.chars().take(PREVIEW_LEN).collect();
+    if shown.len() < first.len() {
+        shown.push('…');
+    }
+    match text.lines().count() {
+        0 | 1 => shown,
+        lines => format!("{shown}  ({lines} lines)"),
+    }
+}
+
+/// A history entry or a register, pasted when clicked.
+fn entry<'a>(label: Option<String>, stored: &str) -> Element<'a, Event> {
+    button(
+        row![]
+            .push_maybe(label.map(|l| text(l).color(iced::color!(0xc2c2c2))))
+            .push(text(preview(stored)))
+            .spacing(10)
+            .align_y(Vertical::Center),
+    )
+    .style(button::text)
+    .width(Length::Fill)
+    .on_press(Event::InsertText(stored.to_string()))
+    .into()
+}
+
+impl Register for ClipboardPanel {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        match event {
+            Event::ToggleClipboard => {
+                self.visible = !self.visible;
+                self.input.clear();
+                if self.visible {
+                    text_input::focus(input_id())
+                } else {
+                    Task::none()
+                }
+            }
+            Event::ClipboardInput(input) => {
+                self.input = input;
+                Task::none()
+            }
+            Event::ClipboardSubmit => match self.register() {
+                Some(name) => Task::done(Event::PasteRegister(name)),
+                None => Task::none(),
+            },
+            Event::InsertText(_)
+            | Event::CopyToRegister(_)
+            | Event::PasteRegister(_) => {
+                self.visible = false;
+                Task::none()
+            }
+            Event::SettingsChanged => {
+                clipboard::set_persisted(settings::current().clipboard.persist);
+                Task::none()
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        if !self.visible {
+            return Column::new().into();
+        }
+        let settings = settings::current();
+
+        let clipboard = clipboard::current();
+        let history = clipboard.history.iter().fold(
+            Column::new().spacing(2),
+            |entries, copied| entries.push(entry(None, copied)),
+        );
+        let named = clipboard.named.iter().fold(
+            Column::new().spacing(2),
+            |entries, (name, stored)| entries.push(entry(Some(name.clone()), stored)),
+        );
+        let register = self.register();
+
+        let header = row![
+            text("Clipboard"),
+            text_input("Register, a to z or A to Z to append", &self.input)
+                .id(input_id())
+                .on_input(Event::ClipboardInput)
+                .on_submit(Event::ClipboardSubmit),
+            button(text("Copy to Register"))
+                .on_press_maybe(register.map(Event::CopyToRegister)),
+            button(text("Paste Register"))
+                .on_press_maybe(register.map(Event::PasteRegister)),
+            labeled_button("Close", Event::ToggleClipboard),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let entries = column![
+            text("History").size(12),
+            history,
+            text("Registers").size(12),
+            named,
+        ]
+        .spacing(6);
+
+        container(
+            column![header, scrollable(entries).height(Length::Fixed(300.0))]
+                .spacing(settings.ui.spacing),
+        )
+        .style(container::bordered_box)
+        .padding(settings.ui.padding)
+        .width(Length::Fill)
+        .into()
+    }
+
+    fn title(&self) -> String {
+        String::from("Clipboard")
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Left
+    }
+
+    fn is_open(&self) -> bool {
+        self.visible
+    }
+}
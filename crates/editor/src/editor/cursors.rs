@@ -0,0 +1,533 @@
+use std::ops::Deref;
+
+use iced::{
+    mouse,
+    widget::{
+        canvas::{self, Frame, Geometry},
+        text_editor::{Edit, Motion},
+    },
+    Color, Point, Rectangle, Renderer, Size, Theme,
+};
+use registers::Event;
+
+/// A place in a buffer. `column` is a byte index inside the line, like the
+/// one reported by `text_editor::Content::cursor_position`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub anchor: Position,
+    pub head: Position,
+}
+
+impl Selection {
+    pub fn new(anchor: Position, head: Position) -> Self {
+        Self { anchor, head }
+    }
+
+    pub fn caret(at: Position) -> Self {
+        Self::new(at, at)
+    }
+
+    pub fn start(&self) -> Position {
+        self.anchor.min(self.head)
+    }
+
+    pub fn end(&self) -> Position {
+        self.anchor.max(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+/// A flat copy of the editor text used to resolve positions and apply
+/// edits on behalf of every cursor at once.
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    text: String,
+    starts: Vec<usize>,
+}
+
+impl Buffer {
+    pub fn new<L: Deref<Target = str>>(lines: impl Iterator<Item = L>) -> Self {
+        let mut text = String::new();
+        let mut starts = Vec::new();
+        for (i, line) in lines.enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            starts.push(text.len());
+            text.push_str(&line);
+        }
+        if starts.is_empty() {
+            starts.push(0);
+        }
+        Self { text, starts }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.starts[line];
+        let end = self
+            .starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        &self.text[start..end]
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let line = position.line.min(self.line_count() - 1);
+        let text = self.line(line);
+        let mut column = position.column.min(text.len());
+        while !text.is_char_boundary(column) {
+            column -= 1;
+        }
+        self.starts[line] + column
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|s| *s <= offset) - 1;
+        Position::new(line, offset - self.starts[line])
+    }
+
+    pub fn slice(&self, selection: &Selection) -> &str {
+        &self.text[self.offset(selection.start())..self.offset(selection.end())]
+    }
+
+    /// Byte column of the `chars`-th character of a line, clamped to its end.
+    pub fn column_at(&self, line: usize, chars: usize) -> usize {
+        let text = self.line(line);
+        text.char_indices().nth(chars).map_or(text.len(), |(i, _)| i)
+    }
+
+    fn chars_before(&self, position: Position) -> usize {
+        let text = self.line(position.line);
+        text[..position.column.min(text.len())].chars().count()
+    }
+
+    fn step(&self, offset: usize, forward: bool) -> usize {
+        if forward {
+            self.text[offset..]
+                .chars()
+                .next()
+                .map_or(offset, |c| offset + c.len_utf8())
+        } else {
+            self.text[..offset]
+                .chars()
+                .next_back()
+                .map_or(offset, |c| offset - c.len_utf8())
+        }
+    }
+
+    fn word(&self, offset: usize, forward: bool) -> usize {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let mut offset = offset;
+        let mut seen_word = false;
+        loop {
+            let next = self.step(offset, forward);
+            if next == offset {
+                return offset;
+            }
+            let c = if forward {
+                self.text[offset..next].chars().next()
+            } else {
+                self.text[next..offset].chars().next()
+            }
+            .unwrap_or(' ');
+            if seen_word && !is_word(c) {
+                return offset;
+            }
+            seen_word |= is_word(c);
+            offset = next;
+        }
+    }
+
+    fn motion(&self, at: Position, motion: Motion) -> Position {
+        let offset = self.offset(at);
+        match motion {
+            Motion::Left => self.position(self.step(offset, false)),
+            Motion::Right => self.position(self.step(offset, true)),
+            Motion::WordLeft => self.position(self.word(offset, false)),
+            Motion::WordRight => self.position(self.word(offset, true)),
+            Motion::Home => Position::new(at.line, 0),
+            Motion::End => Position::new(at.line, self.line(at.line).len()),
+            Motion::Up | Motion::PageUp if at.line > 0 => {
+                self.vertical(at, at.line - 1)
+            }
+            Motion::Down | Motion::PageDown
+                if at.line + 1 < self.line_count() =>
+            {
+                self.vertical(at, at.line + 1)
+            }
+            Motion::Up | Motion::PageUp | Motion::DocumentStart => {
+                Position::default()
+            }
+            Motion::Down | Motion::PageDown | Motion::DocumentEnd => {
+                self.position(self.text.len())
+            }
+        }
+    }
+
+    fn vertical(&self, at: Position, line: usize) -> Position {
+        Position::new(line, self.column_at(line, self.chars_before(at)))
+    }
+}
+
+/// The set of cursors of an editor. The primary selection is mirrored by the
+/// `text_editor::Content`; the others are kept and drawn here.
+#[derive(Debug, Clone, Default)]
+pub struct MultiCursor {
+    selections: Vec<Selection>,
+    primary: usize,
+    last: usize,
+}
+
+impl MultiCursor {
+    pub fn is_active(&self) -> bool {
+        self.selections.len() > 1
+    }
+
+    pub fn clear(&mut self) {
+        self.selections.clear();
+        self.primary = 0;
+        self.last = 0;
+    }
+
+    pub fn primary(&self) -> Option<Selection> {
+        self.selections.get(self.primary).copied()
+    }
+
+    pub fn secondary(&self) -> impl Iterator<Item = &Selection> {
+        let primary = self.primary;
+        self.selections
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| *i != primary)
+            .map(|(_, s)| s)
+    }
+
+    /// Replaces the primary selection with the one shown by the editor.
+    pub fn set_primary(&mut self, selection: Selection) {
+        if self.selections.is_empty() {
+            self.selections.push(selection);
+            self.primary = 0;
+            self.last = 0;
+        } else {
+            self.selections[self.primary] = selection;
+        }
+    }
+
+    fn add(&mut self, selection: Selection) -> bool {
+        if self.selections.iter().any(|s| s.start() == selection.start()) {
+            return false;
+        }
+        self.selections.push(selection);
+        self.last = self.selections.len() - 1;
+        true
+    }
+
+    pub fn add_vertical(&mut self, buffer: &Buffer, below: bool) {
+        let edge = if below {
+            self.selections.iter().max_by_key(|s| s.head.line)
+        } else {
+            self.selections.iter().min_by_key(|s| s.head.line)
+        };
+        let Some(edge) = edge.copied() else {
+            return;
+        };
+        let motion = if below { Motion::Down } else { Motion::Up };
+        let head = buffer.motion(edge.head, motion);
+        if head.line != edge.head.line {
+            self.add(Selection::caret(head));
+        }
+    }
+
+    /// Adds the next occurrence of the most recent selection, wrapping
+    /// around the end of the buffer.
+    pub fn add_next_occurrence(&mut self, buffer: &Buffer) {
+        let Some(last) = self.selections.get(self.last).copied() else {
+            return;
+        };
+        let needle = buffer.slice(&last);
+        if needle.is_empty() {
+            return;
+        }
+        let from = buffer.offset(last.end());
+        let text = buffer.text();
+        let found = text[from..]
+            .match_indices(needle)
+            .map(|(i, _)| i + from)
+            .chain(text[..from].match_indices(needle).map(|(i, _)| i))
+            .find(|start| {
+                let position = buffer.position(*start);
+                !self.selections.iter().any(|s| s.start() == position)
+            });
+        if let Some(start) = found {
+            self.add(Selection::new(
+                buffer.position(start),
+                buffer.position(start + needle.len()),
+            ));
+        }
+    }
+
+    pub fn select_all_occurrences(&mut self, buffer: &Buffer) {
+        let Some(primary) = self.primary() else {
+            return;
+        };
+        let needle = buffer.slice(&primary);
+        if needle.is_empty() {
+            return;
+        }
+        let found: Vec<_> = buffer
+            .text()
+            .match_indices(needle)
+            .map(|(i, _)| {
+                Selection::new(
+                    buffer.position(i),
+                    buffer.position(i + needle.len()),
+                )
+            })
+            .collect();
+        found.into_iter().for_each(|s| {
+            self.add(s);
+        });
+    }
+
+    /// Replaces every cursor with a rectangular selection between two
+    /// (line, character) corners.
+    pub fn block(
+        &mut self,
+        buffer: &Buffer,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) {
+        let last = buffer.line_count() - 1;
+        let (top, bottom) = (from.0.min(to.0).min(last), from.0.max(to.0).min(last));
+        self.clear();
+        for line in top..=bottom {
+            let anchor = Position::new(line, buffer.column_at(line, from.1));
+            let head = Position::new(line, buffer.column_at(line, to.1));
+            self.selections.push(Selection::new(anchor, head));
+        }
+        self.primary = from.0.min(last) - top;
+        self.last = self.selections.len() - 1;
+    }
+
+    /// Moves the secondary cursors; the primary one is moved by the editor.
+    pub fn move_secondary(
+        &mut self,
+        buffer: &Buffer,
+        motion: Motion,
+        select: bool,
+    ) {
+        let primary = self.primary;
+        for (i, selection) in self.selections.iter_mut().enumerate() {
+            if i == primary {
+                continue;
+            }
+            let from = if select || selection.is_empty() {
+                selection.head
+            } else {
+                match motion {
+                    Motion::Left | Motion::Up => selection.start(),
+                    _ => selection.end(),
+                }
+            };
+            let head = if !select && !selection.is_empty()
+                && matches!(motion, Motion::Left | Motion::Right)
+            {
+                from
+            } else {
+                buffer.motion(from, motion)
+            };
+            selection.head = head;
+            if !select {
+                selection.anchor = head;
+            }
+        }
+    }
+
+    /// Applies an edit at every cursor, returning the new text and leaving
+    /// every cursor collapsed after its edit.
+    pub fn edit(&mut self, buffer: &Buffer, edit: &Edit) -> String {
+        let mut order: Vec<usize> = (0..self.selections.len()).collect();
+        order.sort_by_key(|i| self.selections[*i].start());
+
+        // One line of a multi-line paste per cursor, when they match up.
+        let pieces: Option<Vec<&str>> = match edit {
+            Edit::Paste(text) => {
+                let lines: Vec<&str> = text.lines().collect();
+                (lines.len() == order.len()).then_some(lines)
+            }
+            _ => None,
+        };
+
+        let text = buffer.text();
+        let mut output = String::with_capacity(text.len());
+        let mut last = 0;
+        let mut carets = vec![0; self.selections.len()];
+
+        for (n, i) in order.iter().enumerate() {
+            let selection = self.selections[*i];
+            let mut start = buffer.offset(selection.start()).max(last);
+            let mut end = buffer.offset(selection.end()).max(start);
+            if start == end {
+                match edit {
+                    Edit::Backspace => start = buffer.step(start, false).max(last),
+                    Edit::Delete => end = buffer.step(end, true),
+                    _ => {}
+                }
+            }
+            output.push_str(&text[last..start]);
+            match edit {
+                Edit::Insert(c) => output.push(*c),
+                Edit::Enter => output.push('\n'),
+                Edit::Paste(paste) => match &pieces {
+                    Some(pieces) => output.push_str(pieces[n]),
+                    None => output.push_str(paste),
+                },
+                Edit::Backspace | Edit::Delete => {}
+            }
+            carets[*i] = output.len();
+            last = end;
+        }
+        output.push_str(&text[last..]);
+
+        let edited = Buffer::new(output.split('\n'));
+        for (selection, caret) in self.selections.iter_mut().zip(carets) {
+            *selection = Selection::caret(edited.position(caret));
+        }
+        output
+    }
+
+    /// Rectangles to draw for the secondary cursors, in (line, from
+    /// character, to character) form.
+    pub fn spans(&self, buffer: &Buffer) -> Vec<(usize, usize, usize)> {
+        self.secondary()
+            .flat_map(|selection| {
+                let (start, end) = (selection.start(), selection.end());
+                let mut spans = Vec::new();
+                for line in start.line..=end.line.min(buffer.line_count() - 1) {
+                    let from = if line == start.line {
+                        buffer.chars_before(start)
+                    } else {
+                        0
+                    };
+                    let to = if line == end.line {
+                        buffer.chars_before(end)
+                    } else {
+                        buffer.line(line).chars().count()
+                    };
+                    spans.push((line, from, to));
+                }
+                spans
+            })
+            .collect()
+    }
+
+    pub fn carets(&self, buffer: &Buffer) -> Vec<(usize, usize)> {
+        self.secondary()
+            .map(|s| (s.head.line, buffer.chars_before(s.head)))
+            .collect()
+    }
+}
+
+/// Draws the secondary cursors and selections on top of the `text_editor`.
+pub struct CursorOverlay {
+    carets: Vec<(usize, usize)>,
+    spans: Vec<(usize, usize, usize)>,
+    first_line: usize,
+    char_width: f32,
+    line_height: f32,
+    padding: f32,
+}
+
+impl CursorOverlay {
+    pub fn new(
+        cursors: &MultiCursor,
+        buffer: &Buffer,
+        first_line: usize,
+        char_width: f32,
+        line_height: f32,
+        padding: f32,
+    ) -> Self {
+        Self {
+            carets: cursors.carets(buffer),
+            spans: cursors.spans(buffer),
+            first_line,
+            char_width,
+            line_height,
+            padding,
+        }
+    }
+
+    fn point(&self, line: usize, column: usize) -> Option<Point> {
+        let line = line.checked_sub(self.first_line)?;
+        Some(Point::new(
+            self.padding + column as f32 * self.char_width,
+            self.padding + line as f32 * self.line_height,
+        ))
+    }
+}
+
+impl canvas::Program<Event> for CursorOverlay {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let palette = theme.extended_palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        for (line, from, to) in &self.spans {
+            if let Some(point) = self.point(*line, *from) {
+                frame.fill_rectangle(
+                    point,
+                    Size::new(
+                        (to - from) as f32 * self.char_width,
+                        self.line_height,
+                    ),
+                    Color {
+                        a: 0.4,
+                        ..palette.secondary.weak.color
+                    },
+                );
+            }
+        }
+        for (line, column) in &self.carets {
+            if let Some(point) = self.point(*line, *column) {
+                frame.fill_rectangle(
+                    point,
+                    Size::new(1.5, self.line_height),
+                    palette.background.base.text,
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -1,43 +1,204 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use iced::{
     keyboard, 
-    widget::{container, pane_grid, row}, 
+    widget::{container, pane_grid, row, text}, 
+    window,
     Element, Length, Subscription, Task
 };
-use registers::{ Event, Register};
+use registers::{
+    commands, CommandContext, DropTarget, Event, PaneDrag, Register, StatusItem,
+    Target,
+};
+use settings::recent;
 
 use ui::{button_with_icon, danger_button_with_icon, styles, Icon};
-use crate::{pane::Pane, key_bindings::*};
+use crate::{
+    autosave::{self, AutoSave},
+    editor_core::EditorCore, 
+    fileinfo::FileInfo,
+    key_bindings::*, 
+    macros::Macros,
+    pane::Pane, 
+    filesystem::{FileSystem, LocalFs},
+    picker::{FilePicker, NativePicker},
+    preview::Preview, 
+    services::{open_link, pick_folder, save_files}, 
+    session::{layout_order, split_order, Session},
+    status,
+    swap::{self, Swap},
+    zoom,
+};
+
+/// The name addressed events use for the editor.
+pub const NAME: &str = "editor";
+
+/// How often the dirty buffers are copied to the recovery directory.
+const SWAP_INTERVAL: Duration = Duration::from_secs(5);
 
 
 pub struct Editor {
     panes: pane_grid::State<Pane>,
     focus: Option<pane_grid::Pane>,
+    auto_save: AutoSave,
+    last_edit: Option<Instant>,
+    /// The status bar items last sent, to only send them when they change.
+    status: Vec<StatusItem>,
+    /// The git branch of each directory holding an open file.
+    branches: HashMap<PathBuf, Option<String>>,
+    context: CommandContext,
+    /// The file the focused pane showed when the status was last sent.
+    focused: Option<PathBuf>,
+    macros: Macros,
+    picker: Arc<dyn FilePicker>,
+    fs: Arc<dyn FileSystem>,
 }
 
 impl Editor {
     pub fn new() -> Self {
         let (state, grid_panel) = pane_grid::State::new(Pane::new(0));
-        Self {
+        let mut editor = Self {
             panes: state,
             focus: Some(grid_panel),
+            auto_save: AutoSave::from(&settings::current().auto_save),
+            last_edit: None,
+            status: Vec::new(),
+            branches: HashMap::new(),
+            context: CommandContext::default(),
+            focused: None,
+            macros: Macros::load(),
+            picker: Arc::new(NativePicker),
+            fs: Arc::new(LocalFs),
+        };
+        editor.bind_macro_keys();
+        editor
+    }
+
+    /// Rebuilds the panes, tabs and previews saved in a session, reading
+    /// the files from `fs`.
+    pub fn from_session(session: &Session, fs: Arc<dyn FileSystem>) -> Self {
+        let theme = session.theme()
+            .unwrap_or_else(|| settings::current().editor.syntax_theme());
+        let mut id = 0;
+        let configuration = session.configuration(|state| {
+            let mut pane = Pane::new(id);
+            id += 1;
+            pane.core = 
+                EditorCore::from_session(state, theme.clone(), fs.clone());
+            pane.is_pinned = state.is_pinned;
+            pane
+        });
+        
+        let mut panes = pane_grid::State::with_configuration(configuration);
+        let order = layout_order(panes.layout());
+        for (pane, state) in order.iter().zip(session.panes()) {
+            let source = state.preview_of.and_then(|i| order.get(i));
+            if let (Some(source), Some(pane)) = (source, panes.get_mut(*pane)) {
+                pane.preview = Some(Preview::new(*source));
+            }
         }
+        
+        let focus = session.focus
+            .and_then(|i| order.get(i))
+            .or(order.first())
+            .copied();
+        let mut editor = Self {
+            panes,
+            focus,
+            auto_save: AutoSave::from(&settings::current().auto_save),
+            last_edit: None,
+            status: Vec::new(),
+            branches: HashMap::new(),
+            context: CommandContext::default(),
+            focused: None,
+            macros: Macros::load(),
+            picker: Arc::new(NativePicker),
+            fs,
+        };
+        editor.bind_macro_keys();
+        let _ = editor.refresh_previews();
+        editor
+    }
+    
+    pub fn session(&self) -> Session {
+        let theme = self.focus
+            .and_then(|p| self.panes.get(p))
+            .or_else(|| self.panes.iter().next().map(|(_, p)| p))
+            .map_or_else(
+                || settings::current().editor.syntax_theme(), 
+                |p| p.core.theme.clone()
+            );
+        Session::capture(&self.panes, self.focus, theme)
     }
 
     pub fn new_from(t: &Self) -> Self {
         let this = Self {
             panes: t.panes.clone(),
             focus: t.focus,
+            auto_save: t.auto_save,
+            last_edit: t.last_edit,
+            status: t.status.clone(),
+            branches: t.branches.clone(),
+            context: t.context,
+            focused: t.focused.clone(),
+            macros: t.macros.clone(),
+            picker: t.picker.clone(),
+            fs: t.fs.clone(),
         };
         this
     }
 
+    pub fn set_auto_save(&mut self, auto_save: AutoSave) {
+        self.auto_save = auto_save;
+    }
+
+    /// Asks `picker` instead of the native dialogs, in every pane.
+    pub fn set_picker(&mut self, picker: Arc<dyn FilePicker>) {
+        for (_, pane) in self.panes.iter_mut() {
+            pane.core.picker = picker.clone();
+        }
+        self.picker = picker;
+    }
+
+    /// Reads and writes files through `fs` instead of the local disk, in
+    /// every pane. Files already open stay as they were read.
+    pub fn set_filesystem(&mut self, fs: Arc<dyn FileSystem>) {
+        for (_, pane) in self.panes.iter_mut() {
+            pane.core.fs = fs.clone();
+        }
+        self.fs = fs;
+    }
+
+    /// The path and text of every file open in a text tab.
+    pub fn buffers(&self) -> BTreeMap<PathBuf, String> {
+        self.panes.iter()
+            .flat_map(|(_, p)| &p.core.files)
+            .filter(|f| f.kind.is_text() && !f.untitled)
+            .map(|f| (f.path.clone(), f.content.clone()))
+            .collect()
+    }
+
+    pub fn panes(&self) -> &pane_grid::State<Pane> {
+        &self.panes
+    }
+
+    /// The pane editing what the focused one shows.
+    pub fn focused_core(&self) -> Option<&EditorCore> {
+        let focus = self.focus.map(|p| self.source_of(p))?;
+        self.panes.get(focus).map(|p| &p.core)
+    }
+
     pub fn get_key_bindings_map(&self) -> HashMap<char, Vec<KeyBinding>> {
         get_global_hashmap().clone()
     }
     
     pub fn add_key_binding(&mut self, kb: KeyBinding) {
+        commands::bind_shortcut(&kb.event, kb.to_string());
         let mut map = get_global_hashmap();
         map.entry(kb.key)
             .or_insert(Vec::new())
@@ -45,37 +206,74 @@ impl Editor {
     }
     
     pub fn make_key_binding(&mut self, key: char, ctrl: bool, shift: bool, alt: bool, event: Event) {
-        let mut map = get_global_hashmap();
-        map.entry(key)
-            .or_insert(Vec::new())
-            .push(KeyBinding::new(key, ctrl, shift, alt, event.clone()));
+        self.add_key_binding(KeyBinding::new(key, ctrl, shift, alt, event));
     }
     
     pub fn add_keys_bindings(&mut self, bindings: Vec<KeyBinding>) {
         bindings.iter().for_each(move |kb| self.add_key_binding(kb.clone()));
     }
     
-}
-
-impl Register for Editor {
-    fn update(&mut self, _event: Event) -> Task<Event> {
+    /// Binds the saved macros that have a key to playing them once.
+    fn bind_macro_keys(&mut self) {
+        let bindings: Vec<_> = self.macros.keys()
+            .filter_map(|(key, name)| {
+                let play = Event::PlayMacro(Some(name.to_string()), Some(1));
+                let binding = KeyBinding::parse(key, play);
+                if binding.is_none() {
+                    println!("Invalid key {key} for macro {name}");
+                }
+                binding
+            })
+            .collect();
+        self.add_keys_bindings(bindings);
+    }
+    
+    /// Plays the next chunk of the macro being played, asking for the one
+    /// after until it is done.
+    fn resume_macro(&mut self) -> Task<Event> {
+        let Some(id) = self.macros.playing_in() else {
+            return Task::none();
+        };
+        let Some((_, pane)) = self.panes.iter_mut().find(|(_, p)| p.id == id)
+        else {
+            self.macros.stop();
+            return Task::none();
+        };
+        let (task, done) = self.macros.resume(&mut pane.core);
+        if done {
+            Task::batch([task, self.refresh_previews()])
+        } else {
+            task.chain(Task::done(Event::ResumeMacro))
+        }
+    }
+    
+    fn handle(&mut self, _event: Event) -> Task<Event> {
         match _event {
-            Event::PaneDragged(pane_grid::DragEvent::Dropped {
-                pane,
-                target,
-            }) => {
-                self.panes.drop(pane, target);
+            Event::To(target, event) => self.dispatch(target, *event),
+            Event::PaneDragged(PaneDrag::Dropped(id, target)) => {
+                let target = match target {
+                    DropTarget::Edge(edge) => Some(pane_grid::Target::Edge(edge)),
+                    DropTarget::Pane(id, region) => self.grid_pane(id)
+                        .map(|pane| pane_grid::Target::Pane(pane, region)),
+                };
+                if let (Some(pane), Some(target)) = (self.grid_pane(id), target) {
+                    self.panes.drop(pane, target);
+                }
                 Task::none()
             },
             Event::PaneDragged(_) => { Task::none() }
-            Event::TogglePin(pane) => {
-                if let Some(Pane { is_pinned, .. }) = self.panes.get_mut(pane) {
+            Event::TogglePin(id) => {
+                let pane = self.grid_pane(id).and_then(|p| self.panes.get_mut(p));
+                if let Some(Pane { is_pinned, .. }) = pane {
                     *is_pinned = !*is_pinned;
                 }
                 Task::none()
             },
-            Event::Split(axis, pane) => {
-                let state = Pane::new(self.panes.len());
+            Event::Split(axis, id) => {
+                let Some(pane) = self.grid_pane(id) else {
+                    return Task::none();
+                };
+                let state = self.with_services(Pane::new(self.next_pane_id()));
                 let result =
                     self.panes.split(axis, pane, state.clone());
                 
@@ -84,42 +282,492 @@ impl Register for Editor {
                 }
                 Task::none()
             },
-            Event::PaneClicked(pane) => {
+            Event::PaneClicked(id) => {
+                let Some(pane) = self.grid_pane(id) else {
+                    return Task::none();
+                };
+                let left = self.focus.filter(|focus| *focus != pane);
                 self.focus = Some(pane);
-                Task::none()
+                match left {
+                    Some(left) if self.auto_save.on_switch => 
+                        self.auto_save_task(self.unsaved(Some(left))),
+                    _ => Task::none(),
+                }
             },
-            Event::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
-                self.panes.resize(split, ratio);
+            Event::PaneResized(index, ratio) => {
+                let split = split_order(self.panes.layout()).get(index).copied();
+                if let Some(split) = split {
+                    self.panes.resize(split, ratio);
+                }
                 Task::none()
             },
-            Event::Close(pane) => {
-                if let Some((_, sibling)) = self.panes.close(pane) {
+            Event::Close(id) => {
+                let closed = self.grid_pane(id).and_then(|p| self.panes.close(p));
+                if let Some((_, sibling)) = closed {
                     self.focus = Some(sibling);
                 }
+                self.close_orphan_previews();
                 Task::none()
             }
-            _ => {
-                if let Some(p) = self.focus {
-                    let pane = self.panes.get_mut(p).expect("Unable to get pane");
-                    return pane.core.update(_event.clone());
+            Event::TogglePreview(id) => {
+                match id.map_or(self.focus, |id| self.grid_pane(id)) {
+                    Some(pane) => self.toggle_preview(pane),
+                    None => Task::none(),
                 }
-                self.panes.iter_mut().fold(
-                    Task::none(),
-                    |task, (_, e)| {
-                        Task::chain(task,e.core.update(_event.clone()))
+            }
+            Event::OpenFolder => {
+                Task::perform(pick_folder(self.picker.clone()), |folder| {
+                    folder.map_or(Event::None, Event::FolderOpened)
+                })
+            }
+            Event::ReadBuffer(path, reply) => {
+                let text = self.panes.iter()
+                    .flat_map(|(_, p)| &p.core.files)
+                    .find(|f| f.path == path && f.kind.is_text())
+                    .map(|f| Arc::new(f.content.clone()));
+                match text {
+                    Some(text) => Task::done(
+                        Target::register(reply).send(Event::BufferRead(path, text))
+                    ),
+                    None => Task::none(),
+                }
+            }
+            Event::RecordMacro(name) => {
+                self.macros.toggle(name);
+                Task::none()
+            }
+            Event::PlayMacro(name, times) => {
+                let focus = self.focus.map(|p| self.source_of(p));
+                let Some(pane) = focus.and_then(|p| self.panes.get(p)) else {
+                    return Task::none();
+                };
+                self.macros.play(name.as_deref(), pane.id, times);
+                self.resume_macro()
+            }
+            Event::ResumeMacro => self.resume_macro(),
+            Event::BindMacroKey(name) => {
+                self.macros.bind_next(name.as_deref());
+                Task::none()
+            }
+            Event::MacroKeyPressed(key, modifiers) => {
+                let ctrl = modifiers.control() || modifiers.command();
+                let shortcut = KeyBinding::new(
+                    key, ctrl, modifiers.shift(), modifiers.alt(), Event::None
+                ).to_string();
+                if let Some(name) = self.macros.set_key(shortcut) {
+                    self.make_key_binding(
+                        key, ctrl, modifiers.shift(), modifiers.alt(),
+                        Event::PlayMacro(Some(name), Some(1))
+                    );
+                }
+                Task::none()
+            }
+            Event::BindKey(key, modifiers, event) => {
+                self.make_key_binding(
+                    key, modifiers.control(), modifiers.shift(), modifiers.alt(), 
+                    *event
+                );
+                Task::none()
+            }
+            Event::OpenLink(url) => {
+                Task::perform(open_link(url), |_| Event::None)
+            }
+            Event::ThemeChanged(_) 
+            | Event::SetMarkers(..)
+            | Event::GitHead(..)
+            | Event::Reloaded(..)
+            | Event::Saved(_) 
+            | Event::AutoSaved(_) 
+            | Event::ThemesReloaded => self.broadcast(_event),
+            Event::SettingsChanged => {
+                self.auto_save = AutoSave::from(&settings::current().auto_save);
+                self.broadcast(_event)
+            }
+            Event::GlobalZoomIn => {
+                zoom::set_global(zoom::global().zoom_in());
+                self.broadcast(_event)
+            }
+            Event::GlobalZoomOut => {
+                zoom::set_global(zoom::global().zoom_out());
+                self.broadcast(_event)
+            }
+            Event::GlobalZoomReset => {
+                zoom::set_global(zoom::Zoom::ACTUAL);
+                self.broadcast(_event)
+            }
+            Event::AutoSaveTick => {
+                match self.last_edit {
+                    Some(edit) if self.auto_save.is_due(edit) => {
+                        self.last_edit = None;
+                        self.auto_save_task(self.unsaved(None))
                     }
-                )
-            },
+                    _ => Task::none(),
+                }
+            }
+            Event::WindowUnfocused if self.auto_save.on_focus_loss => {
+                self.auto_save_task(self.unsaved(None))
+            }
+            Event::TabSelected(_) if self.auto_save.on_switch => {
+                let task = self.auto_save_task(self.unsaved(self.focus));
+                Task::batch([task, self.forward(_event)])
+            }
+            Event::ScanAllFiles => {
+                self.branches.clear();
+                self.forward(_event)
+            }
+            Event::RefreshBranch => {
+                self.branches.clear();
+                Task::none()
+            }
+            Event::WriteSwaps => {
+                let swaps = self.swaps(false);
+                if swaps.is_empty() {
+                    Task::none()
+                } else {
+                    Task::perform(
+                        swap::write_swaps(self.fs.clone(), swaps),
+                        |_| Event::None,
+                    )
+                }
+            }
+            Event::CloseRequested(_) => {
+                let fs = self.fs.clone();
+                self.session().store(fs.as_ref());
+                self.swaps(true).iter()
+                    .for_each(|swap| swap::write_swap(fs.as_ref(), swap));
+                Task::none()
+            }
+            _ => self.forward(_event),
+        }
+    }
+    
+    /// Sends an addressed event to the pane with the target's id, to the
+    /// panes holding the target's file, or to every pane.
+    fn dispatch(&mut self, target: Target, _event: Event) -> Task<Event> {
+        let Target { pane, file, .. } = target;
+        if pane.is_none() && file.is_none() {
+            return self.broadcast(_event);
         }
+        let tasks: Vec<Task<Event>> = self.panes.iter_mut()
+            .filter(|(_, p)| p.preview.is_none())
+            .filter(|(_, p)| pane.is_none_or(|id| p.id == id))
+            .map(|(_, p)| {
+                let update = match &file {
+                    Some(file) => p.core.update_file(file, _event.clone()),
+                    None => p.core.update(_event.clone()),
+                };
+                // What the event leads to is for the same pane, unless it
+                // is addressed already or meant for the whole window
+                let id = p.id;
+                update.map(move |event| match event {
+                    Event::None | Event::To(..) => event,
+                    event if event.is_app_wide() => event,
+                    event => Target::pane(id).send(event),
+                })
+            })
+            .collect();
+        Task::batch([Task::batch(tasks), self.refresh_previews()])
+    }
+    
+    /// `pane`, asking this editor's picker and using its filesystem.
+    fn with_services(&self, mut pane: Pane) -> Pane {
+        pane.core.picker = self.picker.clone();
+        pane.core.fs = self.fs.clone();
+        pane
+    }
+    
+    /// The pane of the grid with the id events carry.
+    fn grid_pane(&self, id: usize) -> Option<pane_grid::Pane> {
+        self.panes.iter().find(|(_, p)| p.id == id).map(|(pane, _)| *pane)
+    }
+    
+    /// A drag of the grid, with the ids of the panes.
+    fn drag_event(&self, drag: pane_grid::DragEvent) -> Event {
+        let id = |pane| self.panes.get(pane).map(|p: &Pane| p.id);
+        let drag = match drag {
+            pane_grid::DragEvent::Picked { pane } => id(pane).map(PaneDrag::Picked),
+            pane_grid::DragEvent::Dropped { pane, target } => {
+                let target = match target {
+                    pane_grid::Target::Edge(edge) => Some(DropTarget::Edge(edge)),
+                    pane_grid::Target::Pane(pane, region) => 
+                        id(pane).map(|id| DropTarget::Pane(id, region)),
+                };
+                id(pane).zip(target)
+                    .map(|(id, target)| PaneDrag::Dropped(id, target))
+            }
+            pane_grid::DragEvent::Canceled { pane } => 
+                id(pane).map(PaneDrag::Canceled),
+        };
+        drag.map_or(Event::None, Event::PaneDragged)
+    }
+    
+    /// A resize of the grid, with the split numbered in layout order.
+    fn resize_event(&self, resize: pane_grid::ResizeEvent) -> Event {
+        split_order(self.panes.layout())
+            .iter()
+            .position(|split| *split == resize.split)
+            .map_or(Event::None, |index| Event::PaneResized(index, resize.ratio))
+    }
+    
+    /// An id no pane is using.
+    fn next_pane_id(&self) -> usize {
+        self.panes.iter().map(|(_, p)| p.id + 1).max().unwrap_or(0)
+    }
+    
+    fn broadcast(&mut self, _event: Event) -> Task<Event> {
+        let task = Task::batch(
+            self.panes.iter_mut().map(|(_, e)| e.core.update(_event.clone()))
+        );
+        Task::batch([task, self.refresh_previews()])
+    }
+    
+    /// Sends an event to the focused pane, or to every pane without focus.
+    fn forward(&mut self, _event: Event) -> Task<Event> {
+        let task = if let Some(p) = self.focus {
+            let p = self.source_of(p);
+            let pane = self.panes.get_mut(p).expect("Unable to get pane");
+            pane.core.update(_event.clone())
+        } else {
+            Task::batch(
+                self.panes.iter_mut().map(|(_, e)| e.core.update(_event.clone()))
+            )
+        };
+        Task::batch([task, self.refresh_previews()])
+    }
+    
+    /// Sends the status, the command context and the file of the focused
+    /// pane, when they changed since the last time.
+    fn publish_status(&mut self) -> Task<Event> {
+        let focus = self.focus.map(|p| self.source_of(p));
+        let Some(pane) = focus
+            .and_then(|p| self.panes.get(p))
+            .or_else(|| self.panes.iter().next().map(|(_, p)| p))
+        else {
+            return Task::none();
+        };
+        let branch = pane.core.active_path().and_then(|path| {
+            self.branches
+                .entry(status::branch_key(path))
+                .or_insert_with_key(|dir| status::git_branch(self.fs.as_ref(), dir))
+                .clone()
+        });
+        let path = pane.core.active_path().cloned();
+        let focused = if path == self.focused {
+            Task::none()
+        } else {
+            self.focused = path.clone();
+            Task::done(Event::FocusChanged(path))
+        };
+        let context = pane.core.context();
+        let context = if context == self.context {
+            focused
+        } else {
+            self.context = context;
+            focused.chain(Task::done(Event::ContextChanged(context)))
+        };
+        let mut items = status::items(&pane.core, branch.as_deref());
+        if let Some(name) = self.macros.recording() {
+            items.insert(0, StatusItem::left(format!("● Recording {name}"))
+                .on_click(Event::RecordMacro(None)));
+        }
+        if let Some(name) = self.macros.binding() {
+            items.insert(0, StatusItem::left(format!("Press a shortcut for {name}"))
+                .on_click(Event::BindMacroKey(None)));
+        }
+        if items == self.status {
+            return context;
+        }
+        self.status = items.clone();
+        context.chain(Task::done(
+            Event::SetStatusItems(NAME.to_string(), items)
+        ))
+    }
+    
+    /// The buffers auto-save may write: the active one of `pane`, or all of
+    /// them without a pane.
+    fn unsaved(&self, pane: Option<pane_grid::Pane>) -> Vec<FileInfo> {
+        match pane {
+            Some(pane) => self.panes.get(self.source_of(pane))
+                .map(|p| p.core.unsaved(false))
+                .unwrap_or_default(),
+            None => self.panes.iter()
+                .flat_map(|(_, p)| p.core.unsaved(true))
+                .collect(),
+        }
+    }
+    
+    fn auto_save_task(&self, files: Vec<FileInfo>) -> Task<Event> {
+        if files.is_empty() {
+            Task::none()
+        } else {
+            Task::perform(save_files(self.fs.clone(), files), Event::AutoSaved)
+        }
+    }
+    
+    /// The dirty buffers of every pane, see [`EditorCore::take_swaps`].
+    fn swaps(&mut self, all: bool) -> Vec<Swap> {
+        self.panes.iter_mut()
+            .flat_map(|(_, pane)| pane.core.take_swaps(all))
+            .collect()
+    }
+    
+    /// The pane that edits the buffer shown by `pane`.
+    fn source_of(&self, pane: pane_grid::Pane) -> pane_grid::Pane {
+        self.panes.get(pane)
+            .and_then(|p| p.preview.as_ref())
+            .map_or(pane, |preview| preview.source)
+    }
+    
+    fn refresh_previews(&mut self) -> Task<Event> {
+        let previews: Vec<_> = self.panes.iter()
+            .filter(|(_, p)| p.preview.is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        
+        let mut tasks = Vec::new();
+        for id in previews {
+            let Some(mut preview) = 
+                self.panes.get_mut(id).and_then(|p| p.preview.take())
+            else {
+                continue;
+            };
+            if let Some(source) = self.panes.get(preview.source) {
+                tasks.push(preview.sync(&source.core));
+            }
+            if let Some(pane) = self.panes.get_mut(id) {
+                pane.preview = Some(preview);
+            }
+        }
+        Task::batch(tasks)
+    }
+    
+    fn toggle_preview(&mut self, pane: pane_grid::Pane) -> Task<Event> {
+        let source = self.source_of(pane);
+        let existing = self.panes.iter()
+            .find(|(_, p)| p.preview.as_ref().is_some_and(|pr| pr.source == source))
+            .map(|(id, _)| *id);
+        
+        if let Some(preview) = existing {
+            self.panes.close(preview);
+            self.focus = Some(source);
+            return Task::none();
+        }
+        
+        let is_markdown = self.panes.get(source)
+            .and_then(|p| p.core.active_path())
+            .is_some_and(|path| Preview::is_markdown(path));
+        if is_markdown {
+            let state = self.with_services(Pane::preview(self.next_pane_id(), source));
+            self.panes.split(pane_grid::Axis::Vertical, source, state);
+        }
+        self.refresh_previews()
+    }
+    
+    /// Closes the previews whose source pane is gone.
+    fn close_orphan_previews(&mut self) {
+        let orphans: Vec<_> = self.panes.iter()
+            .filter(|(_, p)| p.preview.as_ref()
+                .is_some_and(|pr| self.panes.get(pr.source).is_none()))
+            .map(|(id, _)| *id)
+            .collect();
+        for orphan in orphans {
+            if let Some((_, sibling)) = self.panes.close(orphan) {
+                self.focus = Some(sibling);
+            }
+        }
+    }
+    
+}
+
+impl Register for Editor {
+    fn update(&mut self, _event: Event) -> Task<Event> {
+        let inner = match &_event {
+            Event::To(_, event) => event.as_ref(),
+            event => event,
+        };
+        let persist = matches!(
+            inner,
+            Event::PaneDragged(PaneDrag::Dropped(..))
+                | Event::PaneResized(..)
+                | Event::Split(..)
+                | Event::Close(_)
+                | Event::TogglePin(_)
+                | Event::TogglePreview(_)
+                | Event::Opened(Some(_))
+                | Event::OpenedBinary(..)
+                | Event::Quited(_)
+                | Event::TabSelected(_)
+                | Event::ThemeChanged(_)
+                | Event::Saved(Some(_))
+                | Event::NewFile
+                | Event::Recovered(..)
+        );
+        match inner {
+            Event::Opened(Some((path, _)))
+            | Event::OpenedBinary(path, _)
+            | Event::Saved(Some((_, path, _))) => recent::add_file(path),
+            Event::FolderOpened(path) => recent::add_folder(path),
+            Event::RemoveRecent(path) => recent::remove(path),
+            _ => {}
+        }
+        let edited = match inner {
+            Event::EditorAction(action) => action.is_edit(),
+            Event::Undo 
+            | Event::Redo 
+            | Event::KeyPressed(..) 
+            | Event::ToggleLineEndings
+            | Event::InsertText(_)
+            | Event::ReplaceBuffer(_)
+            | Event::RunScript(_)
+            | Event::PlayMacro(..)
+            | Event::ResumeMacro
+            | Event::Cut
+            | Event::PasteRegister(_) => true,
+            _ => false,
+        };
+        // What reaches the focused pane, addressed events aren't recorded
+        self.macros.record(&_event);
+        if edited && self.auto_save.delay.is_some() {
+            self.last_edit = Some(Instant::now());
+        }
+        let task = self.handle(_event);
+        // Batched, as chaining a none task drops the one before it
+        let task = Task::batch([task, self.publish_status()]);
+        if persist {
+            Task::batch([task, Task::perform(
+                self.session().save(self.fs.clone()),
+                |_| Event::None,
+            )])
+        } else {
+            task
+        }
+    }
+
+    fn route(&mut self, target: Target, event: Event) -> Task<Event> {
+        self.update(target.send(event))
+    }
+    
+    fn name(&self) -> String {
+        String::from(NAME)
     }
 
     fn subscription(&self) -> Subscription<Event> {
-        keyboard::on_key_release(|k,m| {
+        let modifiers = iced::event::listen_with(|event, _status, _window| {
+            match event {
+                iced::Event::Keyboard(keyboard::Event::ModifiersChanged(m)) => 
+                    Some(Event::ModifiersChanged(m)),
+                _ => None,
+            }
+        });
+        
+        let bindings = keyboard::on_key_release(|k,m| {
             let map = get_global_hashmap();
             
             if let keyboard::Key::Character(c) = k {
-                // Get the first character of the key
-                let key = &c.as_str().chars().next().unwrap();
+                // Get the first character of the key, lowercased as shift
+                // reports the uppercase one
+                let key = &c.as_str().chars().next().unwrap()
+                    .to_ascii_lowercase();
                 
                 if let Some(bindings) = map.get(key) {
                     for binding in bindings {
@@ -137,10 +785,52 @@ impl Register for Editor {
             }
             
             None
-        })
+        });
+        
+        // A shortcut with Ctrl or Alt, so typing isn't taken for one
+        let macro_key = if self.macros.binding().is_some() {
+            keyboard::on_key_release(|key, modifiers| match key {
+                keyboard::Key::Character(c)
+                    if modifiers.control() || modifiers.command() || modifiers.alt() =>
+                {
+                    let key = c.chars().next()?.to_ascii_lowercase();
+                    Some(Event::MacroKeyPressed(key, modifiers))
+                }
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+        
+        // Keys no widget captured, for views without a `text_editor`
+        let keys = keyboard::on_key_press(|key, modifiers| {
+            Some(Event::KeyPressed(key, modifiers))
+        });
+        
+        let swaps = iced::time::every(SWAP_INTERVAL).map(|_| Event::WriteSwaps);
+        
+        let unfocused = iced::event::listen_with(|event, _status, _window| {
+            match event {
+                iced::Event::Window(window::Event::Unfocused) => 
+                    Some(Event::WindowUnfocused),
+                _ => None,
+            }
+        });
+        
+        let auto_save = if self.last_edit.is_some() {
+            iced::time::every(autosave::POLL_INTERVAL)
+                .map(|_| Event::AutoSaveTick)
+        } else {
+            Subscription::none()
+        };
+        
+        Subscription::batch([
+            modifiers, bindings, macro_key, keys, swaps, unfocused, auto_save
+        ])
     }
     
     fn view(&self) -> Element<'_, Event> {
+        let settings = settings::current();
         let total_panes = self.panes.len();
         let focus = self.focus;
         
@@ -170,28 +860,51 @@ impl Register for Editor {
                 
                 let (content, label) = if pane.is_pinned { (Icon::Pin, "Unpin") } else { (Icon::Unpin, "Pin") };
                 
+                let previewable = pane.preview.is_some() || pane.core.active_path()
+                    .is_some_and(|path| Preview::is_markdown(path));
+                
+                // Saves this pane's file even when another pane has focus
+                let savable = pane.preview.is_none() 
+                    && pane.core.active_path().is_some();
+                
                 let controls = row![
                     button(
                         content,
                         label,
-                        Event::TogglePin(id)
+                        Event::TogglePin(pane.id)
                     ),
                     button(
                         Icon::HorizontalSplit,
                         "Split Horizontal",
-                        Event::Split(pane_grid::Axis::Horizontal, id),
+                        Event::Split(pane_grid::Axis::Horizontal, pane.id),
                     ),
                     button(
                         Icon::VerticalSplit,
                         "Split Vertical",
-                        Event::Split(pane_grid::Axis::Vertical, id),
+                        Event::Split(pane_grid::Axis::Vertical, pane.id),
+                    )
+                ].push_maybe(savable.then(|| {
+                    button(
+                        Icon::Save,
+                        "Save",
+                        Target::pane(pane.id).send(Event::Save),
                     )
-                ].push_maybe(if total_panes > 1 && !pane.is_pinned {
+                }))
+                .push_maybe(previewable.then(|| {
+                    button_with_icon(
+                        text("MD").size(12),
+                        "Toggle Markdown Preview",
+                        Event::TogglePreview(Some(pane.id)),
+                    )
+                    .padding(4)
+                    .style(styles::tooltip_style)
+                }))
+                .push_maybe(if total_panes > 1 && !pane.is_pinned {
                         Some(
                             danger_button(
                                 Icon::Close,
                                 "Close", 
-                                Event::Close(id)
+                                Event::Close(pane.id)
                             )
                         )
                     } else {
@@ -204,27 +917,32 @@ impl Register for Editor {
                     );
                 
                 pane_grid::Content::new(
-                    pane.core.view()
+                    match &pane.preview {
+                        Some(preview) => preview.view(),
+                        None => pane.core.view(),
+                    }
                 )
                 .title_bar( title_bar )
                 .style(if is_focused {
                     styles::pane_focused
                 }else{
                     styles::pane_active
-                }).into()
+                })
             }).width(Length::Fill)
             .height(Length::Fill)
-            .on_click(Event::PaneClicked)
-            .on_drag(Event::PaneDragged)
-            .on_resize(10, Event::PaneResized)
-            .spacing(5);
+            .on_click(|pane| {
+                self.panes.get(pane).map_or(Event::None, |p| Event::PaneClicked(p.id))
+            })
+            .on_drag(|drag| self.drag_event(drag))
+            .on_resize(10, |resize| self.resize_event(resize))
+            .spacing(settings.ui.spacing);
         
         container(
             grid
         )
         .width(Length::Fill)
         .height(Length::Fill)
-        .padding(4)
+        .padding(settings.ui.padding)
         .into()
     }
 }
@@ -1,24 +1,63 @@
 
-use chrono::Local;
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use chrono::{DateTime, Local};
 use iced::{
     alignment::Horizontal, 
-    color, highlighter, 
-    widget::{column, container, responsive, text, text_editor}, 
-    Element, Font, Length, Task
+    keyboard, 
+    widget::{
+        canvas, column, container, responsive, row, stack, text, 
+        text_editor, text_editor::Binding
+    }, 
+    Element, Length, Point, Task
 };
 use iced_aw::{ TabBar, TabLabel};
-use registers::{ Event, Register};
+use registers::{
+    clipboard, CommandContext, Event, Marker, MarkerKind, Register, SyntaxTheme
+};
+use scripting::ScriptBuffer;
+use settings::EditorSettings;
 use ui::styles;
 
-use crate::fileinfo::FileInfo;
+use crate::cursors::{Buffer, CursorOverlay, MultiCursor, Position, Selection};
+use crate::fileinfo::{FileInfo, FileKind};
+use crate::filesystem::{FileSystem, LocalFs};
+use crate::git;
+use crate::hex::{self, HexState};
+use crate::highlighter::{self, Highlight, SyntaxHighlighter};
+use crate::history::Snapshot;
+use crate::picker::{FilePicker, NativePicker};
+use crate::session::SessionPane;
+use crate::swap::{self, Swap};
+use crate::minimap::Minimap;
+use crate::preview::Preview;
 use crate::services::*;
+use crate::viewer::{self, ViewerState};
+use crate::welcome;
+use crate::zoom::{self, Zoom};
 
 #[derive(Debug)]
 pub struct EditorCore {
     content: text_editor::Content,
     pub files: Vec<FileInfo>,
     pub active_file: usize,
-    pub theme: highlighter::Theme,
+    pub theme: SyntaxTheme,
+    scroll_line: usize,
+    revision: usize,
+    cursors: MultiCursor,
+    modifiers: keyboard::Modifiers,
+    block_origin: Option<(usize, usize)>,
+    viewer: ViewerState,
+    hex: HexState,
+    /// When a buffer of this pane was last auto-saved.
+    auto_saved: Option<DateTime<Local>>,
+    zoom: Zoom,
+    /// The last file that could not be read or written, and why.
+    file_error: Option<(PathBuf, String)>,
+    /// Asks where to open and save files.
+    pub picker: Arc<dyn FilePicker>,
+    /// Where files are read from and written to.
+    pub fs: Arc<dyn FileSystem>,
 }
 
 impl Clone for EditorCore {
@@ -28,6 +67,18 @@ impl Clone for EditorCore {
             files: self.files.clone(),
             active_file: self.active_file,
             theme: self.theme.clone(),
+            scroll_line: self.scroll_line,
+            revision: self.revision,
+            cursors: self.cursors.clone(),
+            modifiers: self.modifiers,
+            block_origin: self.block_origin,
+            viewer: self.viewer,
+            hex: self.hex.clone(),
+            auto_saved: self.auto_saved,
+            zoom: self.zoom,
+            file_error: self.file_error.clone(),
+            picker: self.picker.clone(),
+            fs: self.fs.clone(),
         }
     }
 }
@@ -38,7 +89,19 @@ impl EditorCore {
             content: text_editor::Content::new(),
             files: Vec::new(),
             active_file: 0,
-            theme: highlighter::Theme::Base16Ocean,
+            theme: settings::current().editor.syntax_theme(),
+            scroll_line: 0,
+            revision: 0,
+            cursors: MultiCursor::default(),
+            modifiers: keyboard::Modifiers::default(),
+            block_origin: None,
+            viewer: ViewerState::default(),
+            hex: HexState::default(),
+            auto_saved: None,
+            zoom: Zoom::ACTUAL,
+            file_error: None,
+            picker: Arc::new(NativePicker),
+            fs: Arc::new(LocalFs),
         }
     }
 
@@ -48,6 +111,18 @@ impl EditorCore {
             files: t.files.clone(),
             active_file: t.active_file,
             theme: t.theme.clone(),
+            scroll_line: t.scroll_line,
+            revision: t.revision,
+            cursors: t.cursors.clone(),
+            modifiers: t.modifiers,
+            block_origin: t.block_origin,
+            viewer: t.viewer,
+            hex: t.hex.clone(),
+            auto_saved: t.auto_saved,
+            zoom: t.zoom,
+            file_error: t.file_error.clone(),
+            picker: t.picker.clone(),
+            fs: t.fs.clone(),
         };
         this
     }
@@ -56,28 +131,702 @@ impl EditorCore {
         self.content.text()
     }
     
+    /// Reopens the tabs of a saved pane from `fs`, skipping files that are
+    /// gone.
+    pub fn from_session(
+        state: &SessionPane, 
+        theme: SyntaxTheme, 
+        fs: Arc<dyn FileSystem>,
+    ) -> Self {
+        let mut core = Self::new();
+        core.theme = theme;
+        core.fs = fs;
+        core.zoom = state.zoom;
+        for path in &state.files {
+            match read_path(core.fs.as_ref(), path) {
+                Ok(loaded) => core.files.push(loaded.into_info(path.clone())),
+                Err(e) => println!("Not restoring {:?}: {e}", path),
+            }
+        }
+        core.active_file = state.files.get(state.active_file)
+            .and_then(|active| core.files.iter().position(|f| &f.path == active))
+            .unwrap_or(0);
+        if let Some(file) = core.files.get(core.active_file) {
+            let text = file.content.clone();
+            let (line, column) = state.cursor;
+            core.reload(&text, Position::new(line, column));
+        }
+        core
+    }
+    
+    pub fn cursor_position(&self) -> (usize, usize) {
+        self.content.cursor_position()
+    }
+    
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+    
+    pub fn scroll_line(&self) -> usize {
+        self.scroll_line
+    }
+    
+    pub fn line_count(&self) -> usize {
+        self.content.line_count()
+    }
+    
+    /// Characters in the selection of the primary cursor.
+    pub fn selection_len(&self) -> usize {
+        self.content.selection().map_or(0, |s| s.chars().count())
+    }
+    
+    /// What the active file allows the menus to run.
+    pub fn context(&self) -> CommandContext {
+        let Some(file) = self.files.get(self.active_file) else {
+            return CommandContext::default();
+        };
+        CommandContext {
+            file: true,
+            text: file.kind.is_text(),
+            selection: self.selection_len() > 0 || self.cursors.is_active(),
+            markdown: Preview::is_markdown(&file.path),
+            image: matches!(
+                file.kind, 
+                FileKind::Image { .. } | FileKind::Svg { .. }
+            ),
+        }
+    }
+    
+    pub fn auto_saved(&self) -> Option<DateTime<Local>> {
+        self.auto_saved
+    }
+    
+    pub fn active_path(&self) -> Option<&PathBuf> {
+        self.files.get(self.active_file).map(|f| &f.path)
+    }
+    
+    /// Why the last file that could not be read or written failed.
+    pub fn file_error(&self) -> Option<&str> {
+        self.file_error.as_ref().map(|(_, error)| error.as_str())
+    }
+    
+    /// Hides the file error of `path`, which was read or written since.
+    fn clear_file_error(&mut self, path: &Path) {
+        if self.file_error.as_ref().is_some_and(|(failed, _)| failed == path) {
+            self.file_error = None;
+        }
+    }
+    
+    /// Runs `event` on the open file at `path`, even when another tab is
+    /// shown. Saving, closing, reloading and line ending changes read the
+    /// file as stored; other events work on the buffer being edited, so it
+    /// is loaded for them and the shown tab comes back afterwards.
+    pub fn update_file(&mut self, path: &Path, event: Event) -> Task<Event> {
+        let Some(idx) = self.files.iter().position(|f| f.path == path) else {
+            return Task::none();
+        };
+        if idx == self.active_file {
+            return self.update(event);
+        }
+        let stored = matches!(
+            event,
+            Event::Save
+                | Event::Quit(None)
+                | Event::ScanFile(None)
+                | Event::ToggleLineEndings
+        );
+        if !stored {
+            // The shown tab is put aside as it is, selection and all, while
+            // the other one is loaded at its own caret
+            let shown = std::mem::replace(&mut self.active_file, idx);
+            let content = std::mem::replace(
+                &mut self.content, 
+                text_editor::Content::new()
+            );
+            let scroll_line = std::mem::take(&mut self.scroll_line);
+            let cursors = std::mem::take(&mut self.cursors);
+            let block_origin = self.block_origin.take();
+            let viewer = std::mem::take(&mut self.viewer);
+            let hex = std::mem::take(&mut self.hex);
+            let text = self.files[idx].content.clone();
+            let caret = self.files[idx].cursor;
+            self.reload(&text, caret);
+            let task = self.update(event);
+            // Unless the event itself switched tabs
+            if self.active_file == idx && shown < self.files.len() {
+                self.files[idx].cursor = self.cursor();
+                self.active_file = shown;
+                self.content = content;
+                self.scroll_line = scroll_line;
+                self.cursors = cursors;
+                self.block_origin = block_origin;
+                self.viewer = viewer;
+                self.hex = hex;
+                self.revision += 1;
+            }
+            return task;
+        }
+        let shown = std::mem::replace(&mut self.active_file, idx);
+        let task = self.update(event);
+        self.active_file = shown;
+        task
+    }
+    
+    /// This pane's own zoom, without the global one.
+    pub fn pane_zoom(&self) -> Zoom {
+        self.zoom
+    }
+
+    /// This pane's zoom combined with the global one.
+    pub fn zoom(&self) -> Zoom {
+        self.zoom.with(zoom::global())
+    }
+    
+    /// The editor settings for the language of the active file, zoomed.
+    fn metrics(&self) -> EditorSettings {
+        let language = self.active_path()
+            .and_then(|path| path.extension())
+            .map_or(String::from("txt"), |ext| {
+                ext.to_string_lossy().to_string()
+            });
+        settings::current().editor_for(&language).zoomed(self.zoom().factor())
+    }
+    
+    /// The bytes of the active file, when it is shown in the hex view.
+    fn hex_bytes(&mut self) -> Option<&mut Vec<u8>> {
+        match &mut self.files.get_mut(self.active_file)?.kind {
+            FileKind::Binary(bytes) => Some(Arc::make_mut(bytes)),
+            _ => None,
+        }
+    }
+    
+    /// Swap copies of the dirty text buffers, skipping the ones already 
+    /// written unless `all`.
+    pub fn take_swaps(&mut self, all: bool) -> Vec<Swap> {
+        self.files.iter_mut()
+            .filter(|f| f.kind.is_text() && f.dirty && (all || !f.swapped))
+            .map(|f| {
+                f.swapped = true;
+                Swap::of(f)
+            })
+            .collect()
+    }
+    
+    /// The dirty buffers that auto-save may write: the active one, or every
+    /// one when `all`.
+    pub fn unsaved(&self, all: bool) -> Vec<FileInfo> {
+        self.files.iter()
+            .enumerate()
+            .filter(|(i, _)| all || *i == self.active_file)
+            .map(|(_, f)| f)
+            .filter(|f| f.dirty && !f.untitled)
+            .filter(|f| matches!(f.kind, FileKind::Text | FileKind::Binary(_)))
+            .cloned()
+            .collect()
+    }
+    
+    fn buffer(&self) -> Buffer {
+        Buffer::new(self.content.lines())
+    }
+    
+    fn cursor(&self) -> Position {
+        let (line, column) = self.content.cursor_position();
+        Position::new(line, column)
+    }
+    
+    /// The selection shown by the `text_editor`. Only the selected text is 
+    /// exposed, so the anchor is found by looking at both sides of the cursor.
+    fn primary_selection(&self, buffer: &Buffer) -> Selection {
+        let head = self.cursor();
+        let Some(selected) = self.content.selection().filter(|s| !s.is_empty())
+        else {
+            return Selection::caret(head);
+        };
+        let offset = buffer.offset(head);
+        let before = offset.checked_sub(selected.len())
+            .and_then(|start| buffer.text().get(start..offset));
+        if before == Some(selected.as_str()) {
+            Selection::new(buffer.position(offset - selected.len()), head)
+        } else {
+            Selection::new(buffer.position(offset + selected.len()), head)
+        }
+    }
+    
+    /// Replaces the editor text, leaving the cursor at `caret` and keeping
+    /// the scroll position.
+    fn reload(&mut self, text: &str, caret: Position) {
+        let offset = Buffer::new(text.split('\n')).offset(caret);
+        let (before, after) = text.split_at(offset);
+        self.content = text_editor::Content::with_text(after);
+        if !before.is_empty() {
+            self.content.perform(text_editor::Action::Edit(
+                text_editor::Edit::Paste(Arc::new(before.to_string()))
+            ));
+        }
+        if self.scroll_line > 0 {
+            self.content.perform(text_editor::Action::Scroll {
+                lines: self.scroll_line as i32
+            });
+        }
+        self.revision += 1;
+    }
+    
+    /// Shows the tab at `idx`, remembering where the caret was in the one
+    /// being left.
+    fn show(&mut self, idx: usize) {
+        let caret = self.cursor();
+        if let Some(file) = self.files.get_mut(self.active_file) {
+            file.cursor = caret;
+        }
+        self.active_file = idx;
+    }
+    
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::new(self.files[self.active_file].content.clone(), self.cursor())
+    }
+    
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.cursors.clear();
+        self.reload(&snapshot.text, snapshot.cursor);
+        self.files[self.active_file].content = snapshot.text;
+        self.files[self.active_file].touch();
+    }
+    
+    fn perform(&mut self, action: text_editor::Action) {
+        match &action {
+            text_editor::Action::Edit(edit) => {
+                let kind = match edit {
+                    text_editor::Edit::Insert(_) => Some("insert"),
+                    text_editor::Edit::Backspace => Some("backspace"),
+                    text_editor::Edit::Delete => Some("delete"),
+                    _ => None,
+                };
+                let before = self.snapshot();
+                self.files[self.active_file].history.record(before, kind);
+                self.revision += 1;
+            }
+            text_editor::Action::Scroll { .. } => {}
+            _ => self.files[self.active_file].history.break_group(),
+        }
+        self.content.perform(action);
+    }
+    
+    /// Performs an action on every cursor; edits become one undo step.
+    fn perform_multi(&mut self, action: text_editor::Action) {
+        let buffer = self.buffer();
+        match action {
+            text_editor::Action::Edit(edit) => {
+                let before = self.snapshot();
+                self.files[self.active_file].history.record(before, None);
+                let text = self.cursors.edit(&buffer, &edit);
+                if let Some(primary) = self.cursors.primary() {
+                    self.reload(&text, primary.head);
+                }
+            }
+            text_editor::Action::Move(motion) 
+            | text_editor::Action::Select(motion) => {
+                let select = matches!(action, text_editor::Action::Select(_));
+                let anchor = self.cursors.primary()
+                    .map_or(self.cursor(), |p| p.anchor);
+                self.content.perform(action);
+                self.cursors.move_secondary(&buffer, motion, select);
+                let head = self.cursor();
+                self.cursors.set_primary(if select {
+                    Selection::new(anchor, head)
+                } else {
+                    Selection::caret(head)
+                });
+            }
+            text_editor::Action::Scroll { .. } => self.content.perform(action),
+            _ => {
+                self.cursors.clear();
+                self.content.perform(action);
+            }
+        }
+    }
+    
+    /// Alt + click and drag selects a rectangle of text, one cursor per line.
+    fn block_select(&mut self, action: text_editor::Action) {
+        let metrics = self.metrics();
+        let at = |point: Point| (
+            self.scroll_line 
+                + (point.y.max(0.0) / metrics.line_height_px()) as usize,
+            (point.x.max(0.0) / metrics.char_width()).round() as usize,
+        );
+        match action {
+            text_editor::Action::Click(point) => {
+                self.block_origin = Some(at(point));
+                self.cursors.clear();
+                self.content.perform(action);
+            }
+            text_editor::Action::Drag(point) => {
+                if let Some(origin) = self.block_origin {
+                    let buffer = self.buffer();
+                    self.cursors.block(&buffer, origin, at(point));
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Shows the buffer a macro left, its edits being one undo step.
+    fn apply_script(&mut self, before: &ScriptBuffer, after: ScriptBuffer) {
+        if after.text != before.text {
+            let snapshot = self.snapshot();
+            self.files[self.active_file].history.record(snapshot, None);
+            self.files[self.active_file].content = after.text.clone();
+            self.files[self.active_file].touch();
+        }
+        let buffer = Buffer::new(after.text.split('\n'));
+        self.cursors.clear();
+        self.reload(&after.text, buffer.position(after.anchor));
+        if after.head != after.anchor {
+            self.select_to(&buffer, buffer.position(after.head));
+        }
+    }
+    
+    /// Extends the selection from the caret to `head`. The `text_editor`
+    /// only selects through motions, so it goes a visual line at a time to
+    /// the line of `head`, then a character at a time along that line.
+    fn select_to(&mut self, buffer: &Buffer, head: Position) {
+        let mut last = None;
+        while self.cursor().line != head.line && last != Some(self.cursor()) {
+            let motion = if self.cursor().line < head.line {
+                text_editor::Motion::Down
+            } else {
+                text_editor::Motion::Up
+            };
+            last = Some(self.cursor());
+            self.content.perform(text_editor::Action::Select(motion));
+        }
+        if self.cursor().line != head.line {
+            return;
+        }
+        let column = self.cursor().column;
+        let text = buffer.line(head.line);
+        let (motion, between) = if column < head.column {
+            (text_editor::Motion::Right, text.get(column..head.column))
+        } else {
+            (text_editor::Motion::Left, text.get(head.column..column))
+        };
+        for _ in between.unwrap_or_default().chars() {
+            self.content.perform(text_editor::Action::Select(motion));
+        }
+    }
+    
+    /// Marks the lines of the file at `idx` changed since the last commit
+    /// and, when it is shown, the lines holding the selected text. Keeps
+    /// the markers set by other registers.
+    fn refresh_markers(&self, idx: usize) -> Task<Event> {
+        let Some(file) = self.files.get(idx).filter(|f| f.kind.is_text()) else {
+            return Task::none();
+        };
+        let shown = idx == self.active_file;
+        let selected = self.content.selection()
+            .filter(|s| shown && !s.trim().is_empty() && !s.contains('\n'));
+        let hits = selected.iter().flat_map(|selected| {
+            file.content.lines()
+                .enumerate()
+                .filter(move |(_, line)| line.contains(selected.as_str()))
+                .map(|(i, _)| Marker::new(i, MarkerKind::SearchHit))
+        });
+        let changes = file.head.as_deref()
+            .map(|head| git::change_markers(head, &file.content))
+            .unwrap_or_default();
+        let markers: Vec<_> = file.markers.iter()
+            .filter(|m| !m.kind.is_git())
+            .filter(|m| !shown || m.kind != MarkerKind::SearchHit)
+            .cloned()
+            .chain(hits)
+            .chain(changes)
+            .collect();
+        if markers == file.markers {
+            Task::none()
+        } else {
+            Task::done(Event::SetMarkers(file.path.clone(), markers))
+        }
+    }
+    
+    /// Makes sure the cursor set holds the editor selection before adding
+    /// more cursors.
+    fn sync_primary(&mut self) -> Buffer {
+        let buffer = self.buffer();
+        if !self.cursors.is_active() {
+            self.cursors.clear();
+            self.cursors.set_primary(self.primary_selection(&buffer));
+        }
+        buffer
+    }
+    
 }
 
 impl Register for EditorCore {
     fn update(&mut self, _event: Event) -> Task<Event> {
         match _event {
             Event::EditorAction(action) => {
-                self.content.perform(action.clone());
+                if self.files.is_empty() {
+                    return Task::none();
+                }
+                if action.is_edit() {
+                    self.files[self.active_file].touch();
+                }
+                match action {
+                    text_editor::Action::Scroll { lines } => {
+                        self.scroll_line = self.scroll_line
+                            .saturating_add_signed(lines as isize)
+                            .min(self.content.line_count().saturating_sub(1));
+                        self.content.perform(action);
+                    }
+                    text_editor::Action::Click(_) 
+                    | text_editor::Action::Drag(_) if self.modifiers.alt() => {
+                        self.block_select(action);
+                    }
+                    _ if self.cursors.is_active() => self.perform_multi(action),
+                    _ => self.perform(action),
+                }
+                self.files[self.active_file].content = self.content.text();
+                self.refresh_markers(self.active_file)
+            }
+            Event::AddCursorAbove | Event::AddCursorBelow => {
+                let buffer = self.sync_primary();
+                self.cursors.add_vertical(
+                    &buffer, 
+                    matches!(_event, Event::AddCursorBelow)
+                );
+                Task::none()
+            }
+            Event::AddNextOccurrence | Event::SelectAllOccurrences => {
+                if !self.cursors.is_active() 
+                    && self.content.selection().is_none() 
+                {
+                    self.content.perform(text_editor::Action::SelectWord);
+                    if matches!(_event, Event::AddNextOccurrence) {
+                        return self.refresh_markers(self.active_file);
+                    }
+                }
+                let buffer = self.sync_primary();
+                if matches!(_event, Event::AddNextOccurrence) {
+                    self.cursors.add_next_occurrence(&buffer);
+                } else {
+                    self.cursors.select_all_occurrences(&buffer);
+                }
+                self.refresh_markers(self.active_file)
+            }
+            Event::ClearCursors => {
+                self.cursors.clear();
+                Task::none()
+            }
+            Event::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                if !modifiers.alt() {
+                    self.block_origin = None;
+                }
+                Task::none()
+            }
+            Event::Undo | Event::Redo => {
+                if self.files.is_empty() {
+                    return Task::none();
+                }
+                let current = self.snapshot();
+                let history = &mut self.files[self.active_file].history;
+                let snapshot = if matches!(_event, Event::Undo) {
+                    history.undo(current)
+                } else {
+                    history.redo(current)
+                };
+                if let Some(snapshot) = snapshot {
+                    self.restore(snapshot);
+                }
+                self.refresh_markers(self.active_file)
+            }
+            Event::MinimapScroll(from, to) => {
+                let lines = to as i32 - from as i32;
+                self.content.perform(text_editor::Action::Scroll { lines });
+                self.scroll_line = to;
+                Task::none()
+            }
+            Event::RevealLine(line) => {
+                self.update(Event::MinimapScroll(self.scroll_line, line))
+            }
+            Event::ToggleLineEndings => {
+                if let Some(file) = self.files.get_mut(self.active_file) {
+                    if file.kind.is_text() {
+                        file.crlf = !file.crlf;
+                        file.touch();
+                    }
+                }
+                Task::none()
+            }
+            Event::Copy | Event::Cut => {
+                let Some(selected) =
+                    self.content.selection().filter(|s| !s.is_empty())
+                else {
+                    return Task::none();
+                };
+                clipboard::push(selected.clone());
+                let editable =
+                    self.files.get(self.active_file).is_some_and(|f| f.kind.is_text());
+                if matches!(_event, Event::Cut) && editable {
+                    self.files[self.active_file].touch();
+                    self.perform(text_editor::Action::Edit(text_editor::Edit::Delete));
+                    self.files[self.active_file].content = self.content.text();
+                }
+                iced::clipboard::write(selected)
+            }
+            Event::CopyToRegister(name) => {
+                if let Some(selected) = self.content.selection() {
+                    clipboard::set_register(name, selected);
+                }
+                Task::none()
+            }
+            Event::PasteRegister(name) => match clipboard::register(name) {
+                Some(stored) => self.update(Event::InsertText(stored)),
+                None => Task::none(),
+            },
+            Event::InsertText(text) => {
+                if !self.files.get(self.active_file).is_some_and(|f| f.kind.is_text()) {
+                    return Task::none();
+                }
+                self.files[self.active_file].touch();
+                self.perform(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(text))
+                ));
                 self.files[self.active_file].content = self.content.text();
+                self.refresh_markers(self.active_file)
+            }
+            Event::ReplaceBuffer(text) => {
+                if !self.files.get(self.active_file).is_some_and(|f| f.kind.is_text()) {
+                    return Task::none();
+                }
+                let before = self.snapshot();
+                let caret = before.cursor;
+                self.files[self.active_file].history.record(before, None);
+                self.cursors.clear();
+                self.reload(&text, caret);
+                self.files[self.active_file].content = text;
+                self.files[self.active_file].touch();
+                self.refresh_markers(self.active_file)
+            }
+            Event::RunScript(function) => {
+                let is_text = self.files.get(self.active_file)
+                    .is_some_and(|f| f.kind.is_text());
+                let before = is_text.then(|| {
+                    let buffer = self.buffer();
+                    let selection = self.primary_selection(&buffer);
+                    ScriptBuffer::new(
+                        buffer.text().to_string(),
+                        buffer.offset(selection.anchor),
+                        buffer.offset(selection.head),
+                    )
+                });
+                let (after, events) = 
+                    scripting::run_macro(&function, before.clone());
+                if let (Some(before), Some(after)) = (before, after) {
+                    if after != before {
+                        self.apply_script(&before, after);
+                    }
+                }
+                Task::batch(events.into_iter().map(Task::done))
+            }
+            Event::SetMarkers(path, markers) => {
+                if let Some(file) = self.files.iter_mut().find(|f| f.path == path) {
+                    file.markers = markers;
+                }
                 Task::none()
             }
+            Event::GitHead(path, head) => {
+                let Some(idx) = self.files.iter().position(|f| f.path == path)
+                else {
+                    return Task::none();
+                };
+                self.files[idx].head = head.map(Arc::from);
+                self.refresh_markers(idx)
+            }
             Event::Save => {
-                Task::perform(save_content(EditorCore::new_from(self)), |_| {
-                    Event::Saved
-                })
+                Task::perform(
+                    save_content(EditorCore::new_from(self)), 
+                    std::convert::identity
+                )
             },
-            Event::Saved => {
-                if self.files.len() > 0 {
-                    let file = &mut self.files[self.active_file];
-                    file.last_mod = Local::now();
+            Event::Saved(Some((from, to, digest))) => {
+                let Some(file) = self.files.iter_mut().find(|f| f.path == from)
+                else {
+                    return Task::none();
+                };
+                // A buffer edited while it was written stays dirty, and keeps
+                // its swap unless that is named after the old path
+                let current = digest == file.digest();
+                let moved = from != to;
+                file.path = to.clone();
+                file.untitled = false;
+                file.last_mod = Local::now();
+                if current {
+                    file.dirty = false;
+                } else if moved {
+                    file.swapped = false;
+                }
+                if current || moved {
+                    swap::remove_swap(self.fs.as_ref(), &from);
+                }
+                self.clear_file_error(&from);
+                // Committed since, maybe
+                Task::perform(
+                    git::read_head(self.fs.clone(), to), 
+                    std::convert::identity
+                )
+            }
+            Event::FileError(path, error) => {
+                println!("{error}");
+                self.file_error = Some((path, error));
+                Task::none()
+            }
+            Event::ClearFileError => {
+                self.file_error = None;
+                Task::none()
+            }
+            Event::AutoSaved(saved) => {
+                let now = Local::now();
+                // Buffers edited while they were written stay dirty
+                let saved = self.files.iter_mut().filter(|f| {
+                    saved.iter().any(|(path, digest)| {
+                        *path == f.path && *digest == f.digest()
+                    })
+                });
+                for file in saved {
+                    file.dirty = false;
+                    file.last_mod = now;
+                    swap::remove_swap(self.fs.as_ref(), &file.path);
+                    self.auto_saved = Some(now);
                 }
                 Task::none()
             }
+            Event::NewFile => {
+                let path = swap::untitled_path(self.fs.as_ref());
+                self.files.push(FileInfo::untitled(path, String::new()));
+                self.show(self.files.len() - 1);
+                Task::done(Event::RefreshEditorContent)
+            }
+            Event::Recovered(path, content, untitled) => {
+                let mut info = if untitled {
+                    FileInfo::untitled(swap::untitled_path(self.fs.as_ref()), content)
+                } else {
+                    FileInfo::new(path.clone(), content)
+                };
+                info.touch();
+                let open = self.files.iter()
+                    .position(|f| !untitled && f.path == path);
+                match open {
+                    Some(idx) => {
+                        self.files[idx] = info;
+                        self.show(idx);
+                    }
+                    None => {
+                        self.files.push(info);
+                        self.show(self.files.len() - 1);
+                    }
+                }
+                Task::done(Event::RefreshEditorContent)
+            }
             Event::Quit(idx) => {
                 let i = if let Some(index) = idx {
                     index
@@ -95,11 +844,100 @@ impl Register for EditorCore {
                 })
             }
             Event::OpenFile => {
-                Task::perform(open_file(EditorCore::new_from(self)), |r| {
-                    Event::Opened(r)
-                })
+                Task::perform(
+                    open_file(EditorCore::new_from(self)), 
+                    std::convert::identity
+                )
+            }
+            Event::OpenPath(path) => {
+                if let Some(idx) = self.files.iter().position(|f| f.path == path) {
+                    return Task::done(Event::TabSelected(idx));
+                }
+                Task::perform(
+                    read_file(self.fs.clone(), path), 
+                    |(path, loaded)| opened(path, loaded)
+                )
+            }
+            Event::OpenedBinary(path, bytes) => {
+                self.files.push(FileInfo::binary(path, bytes));
+                self.show(self.files.len() - 1);
+                Task::done(Event::RefreshEditorContent)
+            }
+            Event::ImageZoomIn => {
+                self.viewer.zoom_in();
+                Task::none()
+            }
+            Event::ImageZoomOut => {
+                self.viewer.zoom_out();
+                Task::none()
+            }
+            Event::ImageActualSize => {
+                self.viewer.actual_size();
+                Task::none()
+            }
+            Event::ImageFit => {
+                self.viewer.fit();
+                Task::none()
+            }
+            Event::ZoomIn => {
+                self.zoom = self.zoom.zoom_in();
+                self.revision += 1;
+                Task::none()
+            }
+            Event::ZoomOut => {
+                self.zoom = self.zoom.zoom_out();
+                self.revision += 1;
+                Task::none()
+            }
+            Event::ZoomReset => {
+                self.zoom = Zoom::ACTUAL;
+                self.revision += 1;
+                Task::none()
+            }
+            Event::KeyPressed(key, modifiers) => {
+                let mut hex = std::mem::take(&mut self.hex);
+                if let Some(bytes) = self.hex_bytes() {
+                    if hex.key(bytes, key, modifiers) {
+                        self.revision += 1;
+                        self.files[self.active_file].touch();
+                    }
+                }
+                self.hex = hex;
+                Task::none()
+            }
+            Event::HexSelect(offset) => {
+                let len = self.hex_bytes().map_or(0, |b| b.len());
+                self.hex.move_to(offset, len);
+                Task::none()
+            }
+            Event::HexScroll(rows) => {
+                let len = self.hex_bytes().map_or(0, |b| b.len());
+                self.hex.scroll(rows.round() as i32, len);
+                Task::none()
+            }
+            Event::HexGotoInput(input) => {
+                self.hex.goto = input;
+                Task::none()
+            }
+            Event::HexGoto => {
+                let len = self.hex_bytes().map_or(0, |b| b.len());
+                self.hex.goto(len);
+                Task::none()
+            }
+            Event::HexPatternInput(input) => {
+                self.hex.pattern = input;
+                Task::none()
+            }
+            Event::HexFindNext => {
+                let mut hex = std::mem::take(&mut self.hex);
+                if let Some(bytes) = self.hex_bytes() {
+                    hex.find_next(bytes);
+                }
+                self.hex = hex;
+                Task::none()
             }
             Event::Quited(_path) => {
+                swap::remove_swap(self.fs.as_ref(), &_path);
                 for (i, f) in self.files.iter().enumerate() {
                     if f.path.to_str().unwrap() == _path.to_str().unwrap() {
                         self.files.remove(i);
@@ -115,9 +953,14 @@ impl Register for EditorCore {
                 Task::done(Event::RefreshEditorContent)
             }
             Event::Opened(Option::Some((path, content))) => {
-                self.files.push(FileInfo::new(path, content));
-                self.active_file = self.files.len() - 1;
-                Task::done(Event::RefreshEditorContent)
+                self.clear_file_error(&path);
+                self.files.push(FileInfo::new(path.clone(), content));
+                self.show(self.files.len() - 1);
+                let head = Task::perform(
+                    git::read_head(self.fs.clone(), path), 
+                    std::convert::identity
+                );
+                Task::batch([Task::done(Event::RefreshEditorContent), head])
             }
             Event::RefreshEditorContent => {
                 if self.files.len() > 0 {
@@ -126,53 +969,104 @@ impl Register for EditorCore {
                 } else {
                     self.content = text_editor::Content::new();
                 }
+                self.scroll_line = 0;
+                self.revision += 1;
+                self.cursors.clear();
+                self.viewer = ViewerState::default();
+                self.hex = HexState::default();
                 Task::none()
             }
             Event::ScanFile(path) => {
-                let idx = 
-                    scan_file(EditorCore::new_from(self), path);
-                Task::perform(idx, |need_refresh| {
-                    if need_refresh {
-                        Event::RefreshEditorContent
-                    } else {
-                        Event::None
-                    }
-                })
+                let path = path.or_else(|| self.active_path().cloned());
+                let scanned = path.filter(|path| {
+                    self.files.iter().any(|f| {
+                        &f.path == path && f.kind.is_text() && !f.untitled
+                    })
+                });
+                match scanned {
+                    Some(path) => Task::perform(
+                        scan_file(self.fs.clone(), path), 
+                        std::convert::identity
+                    ),
+                    None => Task::none(),
+                }
             }
             Event::ScanAllFiles => {
-                self.files.iter_mut().for_each(|f| {
-                    f.content = std::fs::read_to_string(f.path.clone())
-                        .expect("Unable to read file");
-                });
-
-                Task::done(Event::RefreshEditorContent)
+                Task::batch(
+                    self.files.iter()
+                        .filter(|f| f.kind.is_text() && !f.untitled)
+                        .map(|f| Task::perform(
+                            scan_file(self.fs.clone(), f.path.clone()), 
+                            std::convert::identity
+                        ))
+                )
+            }
+            Event::Reloaded(path, content) => {
+                let Some(idx) = self.files.iter().position(|f| f.path == path)
+                else {
+                    return Task::none();
+                };
+                let file = &mut self.files[idx];
+                file.crlf = content.contains("\r\n");
+                file.content = content;
+                file.dirty = false;
+                swap::remove_swap(self.fs.as_ref(), &path);
+                self.clear_file_error(&path);
+                let shown = idx == self.active_file;
+                let head = Task::perform(
+                    git::read_head(self.fs.clone(), path), 
+                    std::convert::identity
+                );
+                if shown {
+                    Task::batch([Task::done(Event::RefreshEditorContent), head])
+                } else {
+                    head
+                }
             }
             Event::TabSelected(idx) => {
-                self.active_file = idx;
+                self.show(idx);
                 Task::done(Event::RefreshEditorContent)
             }
             Event::TabClosed(idx) => Task::done(Event::Quit(Some(idx))),
             Event::NewTab => Task::done(Event::OpenFile),
+            Event::NextTab | Event::PreviousTab if self.files.len() > 1 => {
+                let len = self.files.len();
+                let step = if matches!(_event, Event::NextTab) { 1 } else { len - 1 };
+                Task::done(Event::TabSelected((self.active_file + step) % len))
+            }
             Event::ThemeChanged(theme) => {
                 self.theme = theme;
+                self.revision += 1;
                 Task::none()
             },
+            Event::SettingsChanged 
+            | Event::ThemesReloaded 
+            | Event::GlobalZoomIn 
+            | Event::GlobalZoomOut 
+            | Event::GlobalZoomReset => {
+                self.revision += 1;
+                Task::none()
+            }
             _ => Task::none(),
         }
     }
     
     fn view(&self) -> Element<'_, Event> {
-        let cursor = self.content.cursor_position();
-        let num_lines = self.content.line_count();
         let files = &self.files;
         let active_file = self.active_file;
         let theme = self.theme.clone();
         let content = &self.content;
+        let scroll_line = self.scroll_line;
+        let revision = self.revision;
+        let cursors = &self.cursors;
+        let viewer = self.viewer;
+        let hex = &self.hex;
+        let metrics = self.metrics();
         
         container(
             create_editor(
-                cursor, num_lines, files.clone(), 
-                active_file, theme, content
+                files.clone(), active_file, theme, content,
+                scroll_line, revision, cursors, viewer, hex, metrics
             )
         )
         .width(Length::Fill)
@@ -182,14 +1076,18 @@ impl Register for EditorCore {
     }
 }
 
-fn create_editor( 
-    cursor: (usize, usize), 
-    num_lines: usize,
+fn create_editor<'a>( 
     files: Vec<FileInfo>, 
     active_file: usize, 
-    theme: highlighter::Theme, 
-    content: &text_editor::Content
-) -> Element<'_, Event> {
+    theme: SyntaxTheme, 
+    content: &'a text_editor::Content,
+    scroll_line: usize,
+    revision: usize,
+    cursors: &'a MultiCursor,
+    viewer: ViewerState,
+    hex: &'a HexState,
+    metrics: EditorSettings,
+) -> Element<'a, Event> {
     responsive(move |s| {
         let tabs = column!(
             files
@@ -223,34 +1121,89 @@ fn create_editor(
                 "txt".to_string()
         };
         
+        let multi = cursors.is_active();
         let mut editor = 
-            text_editor(&content)
-            .font(Font::MONOSPACE)
-            .highlight(languaje.as_str(), theme.clone())
+            text_editor(content)
+            .font(metrics.font())
+            .highlight_with::<SyntaxHighlighter>(
+                highlighter::Settings::new(theme.clone(), languaje.clone()),
+                Highlight::to_format,
+            )
             .style(styles::editor_style)
             .wrapping(text::Wrapping::WordOrGlyph)
-            .size(16)
+            .size(metrics.font_size)
+            .line_height(text::LineHeight::Relative(metrics.line_height))
+            .padding(metrics.padding)
+            .height(Length::Fill)
+            .key_binding(move |key_press| {
+                let focused = key_press.status == text_editor::Status::Focused;
+                let modifiers = key_press.modifiers;
+                let vertical = focused && modifiers.control() && modifiers.alt();
+                match key_press.key.as_ref() {
+                    keyboard::Key::Named(keyboard::key::Named::ArrowUp) 
+                        if vertical => 
+                    {
+                        Some(Binding::Custom(Event::AddCursorAbove))
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::ArrowDown) 
+                        if vertical => 
+                    {
+                        Some(Binding::Custom(Event::AddCursorBelow))
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::Escape) 
+                        if focused && multi => 
+                    {
+                        Some(Binding::Custom(Event::ClearCursors))
+                    }
+                    // Through the editor, to keep them in the history
+                    _ => match Binding::from_key_press(key_press) {
+                        Some(Binding::Copy) => Some(Binding::Custom(Event::Copy)),
+                        Some(Binding::Cut) => Some(Binding::Custom(Event::Cut)),
+                        binding => binding,
+                    },
+                }
+            });
+        
+        let markers = files.get(active_file)
+            .map(|info| info.markers.clone())
+            .unwrap_or_default();
+        let minimap = canvas(
+            Minimap::new(
+                content, markers, theme.clone(), languaje.clone(),
+                scroll_line, metrics.line_height_px(), revision
+            ))
+            .width(Length::Fixed(90.0))
             .height(Length::Fill);
     
-        let mut texto = "".to_string();
-        if files.len() > 0 {
+        let kind = files.get(active_file)
+            .map_or(FileKind::Text, |info| info.kind.clone());
+        
+        if files.len() > 0 && kind.is_text() {
             editor = editor.on_action(Event::EditorAction);
-            let active_info = &files[active_file];
-            let last_mod = active_info.last_mod.format("%v %r (%s timestamp)");
-            let (col, row) = cursor;
-            texto = format!(
-                "{last_mod} | Lines {num_lines} | Cursor: {col} {row}"
-            );
         }
         
-        let indicator = text(texto)
-        .color(iced::color!(0xc2c2c2))
-        .font(Font::DEFAULT)
-        .line_height(text::LineHeight::Relative(2.0))
-        .align_x(Horizontal::Right)
-        .width(Length::Fill);
+        let editor: Element<'_, Event> = if multi {
+            let overlay = CursorOverlay::new(
+                cursors, &Buffer::new(content.lines()), scroll_line, 
+                metrics.char_width(), metrics.line_height_px(), 
+                metrics.padding
+            );
+            stack![
+                editor, 
+                canvas(overlay).width(Length::Fill).height(Length::Fill)
+            ].into()
+        } else {
+            editor.into()
+        };
+        
+        let body: Element<'_, Event> = match kind {
+            _ if files.is_empty() => welcome::view(),
+            FileKind::Text => row![editor, minimap].spacing(4).into(),
+            FileKind::Binary(bytes) => hex::view(bytes, hex.clone(), &metrics),
+            _ => viewer::view(kind, viewer),
+        };
         
-            column![tabs, editor, indicator]
+            column![tabs, body]
                 .width(Length::FillPortion(s.width as u16))
                 .height(Length::FillPortion(s.height as u16))
                 .align_x(Horizontal::Center)
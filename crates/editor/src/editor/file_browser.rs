@@ -0,0 +1,389 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+};
+
+use iced::{
+    alignment::Vertical,
+    futures::{
+        channel::{mpsc, oneshot},
+        future::BoxFuture,
+        stream::{self, BoxStream},
+        FutureExt, StreamExt,
+    },
+    keyboard::{self, key::Named},
+    widget::{
+        button, column, container, row, scrollable, text, text_input, Column,
+    },
+    Element, Length, Subscription, Task,
+};
+use registers::{Dock, Event, Register};
+use ui::labeled_button;
+
+use crate::{filesystem::FileSystem, picker::FilePicker};
+
+/// Entries listed at once.
+const MAX_ENTRIES: usize = 500;
+
+fn input_id() -> text_input::Id {
+    text_input::Id::new("file-browser")
+}
+
+/// What a dialog asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Purpose {
+    File,
+    Folder,
+    /// Where to write a file, suggesting a name.
+    Save(String),
+}
+
+/// A dialog waiting for the browser to answer.
+struct Request {
+    purpose: Purpose,
+    answer: oneshot::Sender<Option<PathBuf>>,
+}
+
+struct Dialogs {
+    pending: Option<Request>,
+    asked: mpsc::UnboundedSender<()>,
+    /// Taken by the subscription of the browser, told of every request.
+    listener: Option<mpsc::UnboundedReceiver<()>>,
+}
+
+fn dialogs() -> MutexGuard<'static, Dialogs> {
+    static DIALOGS: OnceLock<Mutex<Dialogs>> = OnceLock::new();
+    DIALOGS
+        .get_or_init(|| {
+            let (asked, listener) = mpsc::unbounded();
+            Mutex::new(Dialogs {
+                pending: None,
+                asked,
+                listener: Some(listener),
+            })
+        })
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+/// Answers the file dialogs with the [`FileBrowser`], instead of the
+/// dialogs of the system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InAppPicker;
+
+impl InAppPicker {
+    fn ask(purpose: Purpose) -> BoxFuture<'static, Option<PathBuf>> {
+        let (answer, answered) = oneshot::channel();
+        let mut dialogs = dialogs();
+        // A dialog still waiting is cancelled by the new one
+        dialogs.pending = Some(Request { purpose, answer });
+        let _ = dialogs.asked.unbounded_send(());
+        drop(dialogs);
+        async move { answered.await.ok().flatten() }.boxed()
+    }
+}
+
+impl FilePicker for InAppPicker {
+    fn pick_file(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        Self::ask(Purpose::File)
+    }
+
+    fn pick_folder(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        Self::ask(Purpose::Folder)
+    }
+
+    fn save_file(&self, name: &str) -> BoxFuture<'static, Option<PathBuf>> {
+        let name = Path::new(name)
+            .file_name()
+            .map_or(String::new(), |n| n.to_string_lossy().to_string());
+        Self::ask(Purpose::Save(name))
+    }
+}
+
+/// Sends `ShowFileBrowser` each time an [`InAppPicker`] asks.
+fn asked() -> BoxStream<'static, Event> {
+    match dialogs().listener.take() {
+        Some(listener) => listener.map(|()| Event::ShowFileBrowser).boxed(),
+        None => stream::pending().boxed(),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+}
+
+/// Browses folders from the keyboard to answer an [`InAppPicker`]. Typing
+/// filters the entries of the folder, or names a path ending with `/` to
+/// go there. The arrows select, Tab completes, Enter opens and Escape,
+/// once the input lost focus, cancels.
+pub struct FileBrowser {
+    /// What the waiting dialog asks for, `None` when hidden.
+    purpose: Option<Purpose>,
+    dir: PathBuf,
+    input: String,
+    /// The folders then the files of `dir`.
+    entries: Vec<Entry>,
+    selected: usize,
+    fs: Arc<dyn FileSystem>,
+}
+
+impl FileBrowser {
+    pub fn new(fs: Arc<dyn FileSystem>) -> Self {
+        Self {
+            purpose: None,
+            dir: PathBuf::new(),
+            input: String::new(),
+            entries: Vec::new(),
+            selected: 0,
+            fs,
+        }
+    }
+
+    fn show(&mut self) -> Task<Event> {
+        self.purpose = dialogs().pending.as_ref().map(|r| r.purpose.clone());
+        let Some(purpose) = &self.purpose else {
+            return Task::none();
+        };
+        let input = match purpose {
+            Purpose::Save(name) => name.clone(),
+            _ => String::new(),
+        };
+        let dir = settings::recent::start_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("/"));
+        self.enter(dir);
+        self.input = input;
+        text_input::focus(input_id())
+    }
+
+    fn enter(&mut self, dir: PathBuf) {
+        let folders_only = self.purpose == Some(Purpose::Folder);
+        let mut entries: Vec<Entry> = self
+            .fs
+            .list_dir(&dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| Entry {
+                name: path
+                    .file_name()
+                    .map_or(String::new(), |n| n.to_string_lossy().to_string()),
+                is_dir: self.fs.is_dir(&path),
+                path,
+            })
+            .filter(|e| e.is_dir || !folders_only)
+            .collect();
+        entries.sort_by_key(|e| (!e.is_dir, e.name.to_lowercase()));
+        self.entries = entries;
+        self.dir = dir;
+        self.input.clear();
+        self.selected = 0;
+    }
+
+    /// The path typed in the input, from the browsed folder.
+    fn typed(&self) -> PathBuf {
+        let input = self.input.trim();
+        match (input.strip_prefix('~'), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+            _ => self.dir.join(input),
+        }
+    }
+
+    /// The entries matching the input, with the parent folder first when
+    /// nothing is typed. Hidden ones are listed once a `.` is typed.
+    fn matches(&self) -> Vec<Entry> {
+        let input = self.input.trim().to_lowercase();
+        let parent =
+            self.dir
+                .parent()
+                .filter(|_| input.is_empty())
+                .map(|parent| Entry {
+                    path: parent.to_path_buf(),
+                    name: String::from(".."),
+                    is_dir: true,
+                });
+        let listed = self
+            .entries
+            .iter()
+            .filter(|e| input.starts_with('.') || !e.name.starts_with('.'))
+            .filter(|e| e.name.to_lowercase().contains(&input))
+            .cloned();
+        parent.into_iter().chain(listed).take(MAX_ENTRIES).collect()
+    }
+
+    fn answer(&mut self, path: Option<PathBuf>) -> Task<Event> {
+        if let Some(request) = dialogs().pending.take() {
+            let _ = request.answer.send(path);
+        }
+        self.purpose = None;
+        Task::none()
+    }
+
+    /// Goes into a folder, or answers with a file.
+    fn pick(&mut self, path: PathBuf) -> Task<Event> {
+        if self.fs.is_dir(&path) {
+            self.enter(path);
+            text_input::focus(input_id())
+        } else {
+            self.answer(Some(path))
+        }
+    }
+
+    fn submit(&mut self) -> Task<Event> {
+        let typed = self.typed();
+        let nothing_typed = self.input.trim().is_empty();
+        match &self.purpose {
+            Some(Purpose::Folder) if nothing_typed => {
+                let dir = self.dir.clone();
+                return self.answer(Some(dir));
+            }
+            _ if !nothing_typed && self.fs.is_dir(&typed) => {
+                self.enter(typed);
+                return text_input::focus(input_id());
+            }
+            Some(Purpose::Save(_)) if !nothing_typed => {
+                return self.answer(Some(typed));
+            }
+            Some(Purpose::File) if self.fs.metadata(&typed).is_ok() => {
+                return self.answer(Some(typed));
+            }
+            _ => {}
+        }
+        match self.matches().into_iter().nth(self.selected) {
+            Some(entry) => self.pick(entry.path),
+            None => Task::none(),
+        }
+    }
+
+    fn key(&mut self, key: keyboard::Key) -> Task<Event> {
+        let count = self.matches().len();
+        match key {
+            keyboard::Key::Named(Named::ArrowUp) => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            keyboard::Key::Named(Named::ArrowDown) => {
+                self.selected =
+                    (self.selected + 1).min(count.saturating_sub(1));
+            }
+            keyboard::Key::Named(Named::Tab) => {
+                match self.matches().into_iter().nth(self.selected) {
+                    Some(entry) if entry.is_dir => self.enter(entry.path),
+                    Some(entry) => self.input = entry.name,
+                    None => {}
+                }
+                return text_input::focus(input_id());
+            }
+            keyboard::Key::Named(Named::Escape) => return self.answer(None),
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+impl Register for FileBrowser {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        match event {
+            Event::ShowFileBrowser => self.show(),
+            _ if self.purpose.is_none() => Task::none(),
+            Event::FileBrowserInput(input) => {
+                self.input = input;
+                self.selected = 0;
+                if self.input.ends_with('/') && self.fs.is_dir(&self.typed()) {
+                    self.enter(self.typed());
+                }
+                Task::none()
+            }
+            Event::FileBrowserSubmit => self.submit(),
+            Event::FileBrowserPick(path) => self.pick(path),
+            Event::FileBrowserCancel => self.answer(None),
+            Event::KeyPressed(key, _) => self.key(key),
+            _ => Task::none(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        let Some(purpose) = &self.purpose else {
+            return Column::new().into();
+        };
+        let settings = settings::current();
+        let (title, action, placeholder) = match purpose {
+            Purpose::File => ("Open File", "Open", "File name, or a path"),
+            Purpose::Folder => {
+                ("Open Folder", "Open", "Folder name, Enter for this one")
+            }
+            Purpose::Save(_) => ("Save As", "Save", "File name"),
+        };
+
+        let entries = self.matches().into_iter().enumerate().fold(
+            Column::new().spacing(2),
+            |entries, (i, entry)| {
+                let name = if entry.is_dir {
+                    format!("{}/", entry.name)
+                } else {
+                    entry.name
+                };
+                let style = if i == self.selected {
+                    button::primary
+                } else {
+                    button::text
+                };
+                entries.push(
+                    button(text(name))
+                        .style(style)
+                        .width(Length::Fill)
+                        .on_press(Event::FileBrowserPick(entry.path)),
+                )
+            },
+        );
+
+        let header = row![
+            text(title),
+            text(self.dir.display().to_string()).color(iced::color!(0xc2c2c2)),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center);
+
+        let input = row![
+            text_input(placeholder, &self.input)
+                .id(input_id())
+                .on_input(Event::FileBrowserInput)
+                .on_submit(Event::FileBrowserSubmit),
+            labeled_button(action, Event::FileBrowserSubmit),
+            labeled_button("Cancel", Event::FileBrowserCancel),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let hint = text("↑↓ select, Tab completes, Enter opens, Esc cancels")
+            .size(12)
+            .color(iced::color!(0xc2c2c2));
+
+        container(
+            column![
+                header,
+                input,
+                scrollable(entries).height(Length::Fixed(300.0)),
+                hint
+            ]
+            .spacing(settings.ui.spacing),
+        )
+        .style(container::bordered_box)
+        .padding(settings.ui.padding)
+        .width(Length::Fill)
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Event> {
+        Subscription::run(asked)
+    }
+
+    fn title(&self) -> String {
+        String::from("File Browser")
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Top
+    }
+}
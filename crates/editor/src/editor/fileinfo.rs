@@ -1,15 +1,140 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 use chrono::{DateTime, Local};
+use iced::widget::{image, svg};
+use registers::Marker;
+
+use crate::cursors::Position;
+use crate::history::History;
+
+const IMAGE_EXTENSIONS: [&str; 9] = 
+    ["png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "tif", "tiff"];
+
+/// What an open file holds, which decides how its tab is displayed.
+#[derive(Debug, Clone)]
+pub enum FileKind {
+    Text,
+    Image { handle: image::Handle, size: Option<(u32, u32)> },
+    Svg { handle: svg::Handle, size: Option<(u32, u32)> },
+    Binary(Arc<Vec<u8>>),
+}
+
+impl FileKind {
+    /// Classifies the bytes of a file that isn't valid text.
+    pub fn detect(path: &std::path::Path, bytes: Arc<Vec<u8>>) -> Self {
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        
+        if extension == "svg" {
+            let size = usvg::Tree::from_data(&bytes, &usvg::Options::default())
+                .ok()
+                .map(|tree| {
+                    let size = tree.size();
+                    (size.width().round() as u32, size.height().round() as u32)
+                });
+            Self::Svg { 
+                handle: svg::Handle::from_memory(bytes.to_vec()), 
+                size 
+            }
+        } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            let size = ::image::io::Reader::new(std::io::Cursor::new(&bytes[..]))
+                .with_guessed_format()
+                .ok()
+                .and_then(|reader| reader.into_dimensions().ok());
+            Self::Image { 
+                handle: image::Handle::from_bytes(bytes.to_vec()), 
+                size 
+            }
+        } else {
+            Self::Binary(bytes)
+        }
+    }
+    
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Text)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub path: PathBuf,
     pub content: String,
-    pub last_mod: DateTime<Local>
+    pub last_mod: DateTime<Local>,
+    pub markers: Vec<Marker>,
+    pub history: History,
+    pub kind: FileKind,
+    /// Never saved, so `path` is only a display name.
+    pub untitled: bool,
+    /// Edited since it was last saved.
+    pub dirty: bool,
+    /// The current content is already in the recovery directory.
+    pub swapped: bool,
+    /// Written with `\r\n` line endings. The content always uses `\n`
+    /// once edited.
+    pub crlf: bool,
+    /// Where the caret was when another tab was last shown.
+    pub cursor: Position,
+    /// What the file holds in the last commit, to mark the lines changed
+    /// since.
+    pub head: Option<Arc<str>>,
 }
 
 impl FileInfo {
     pub fn new(path: PathBuf, content: String) -> Self {
-        Self { path, content, last_mod: Local::now() }
+        Self { 
+            path, 
+            crlf: content.contains("\r\n"),
+            content, 
+            last_mod: Local::now(), 
+            markers: Vec::new(),
+            history: History::default(),
+            kind: FileKind::Text,
+            untitled: false,
+            dirty: false,
+            swapped: false,
+            cursor: Position::default(),
+            head: None,
+        }
+    }
+    
+    pub fn untitled(path: PathBuf, content: String) -> Self {
+        Self {
+            untitled: true,
+            ..Self::new(path, content)
+        }
+    }
+    
+    /// Marks the buffer as edited since it was last saved.
+    pub fn touch(&mut self) {
+        self.dirty = true;
+        self.swapped = false;
+    }
+    
+    /// A hash of what saving the buffer writes, to tell whether it was
+    /// edited since.
+    pub fn digest(&self) -> u64 {
+        match &self.kind {
+            FileKind::Binary(bytes) => fnv1a(bytes),
+            _ => fnv1a(self.content.as_bytes()),
+        }
     }
-}
\ No newline at end of file
+    
+    pub fn binary(path: PathBuf, bytes: Arc<Vec<u8>>) -> Self {
+        let kind = FileKind::detect(&path, bytes);
+        Self {
+            kind,
+            ..Self::new(path, String::new())
+        }
+    }
+}
+
+/// The 64 bits FNV-1a hash of `bytes`, which unlike the hasher of the
+/// standard library stays the same from one build to the next.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
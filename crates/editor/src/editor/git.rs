@@ -0,0 +1,83 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    time::Duration,
+};
+
+use registers::{Event, Marker, MarkerKind};
+use similar::{DiffOp, TextDiff};
+
+use crate::filesystem::FileSystem;
+
+/// How long a buffer is compared with its committed version before the
+/// diff settles for a coarser result.
+const DIFF_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// The folder of the repository in `fs` holding `path`.
+fn repository(fs: &dyn FileSystem, path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| fs.metadata(&dir.join(".git")).is_ok())
+        .map(Path::to_path_buf)
+}
+
+/// What the file at `path` holds in the last commit, `None` when it is
+/// not in a repository or not committed. Asks `git`, away from the UI
+/// thread.
+pub async fn read_head(fs: Arc<dyn FileSystem>, path: PathBuf) -> Event {
+    smol::unblock(move || {
+        let head = repository(fs.as_ref(), &path).and_then(|root| {
+            let relative = path.strip_prefix(&root).ok()?;
+            let object = relative.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(["show", &format!("HEAD:{object}")])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            String::from_utf8(output.stdout).ok()
+        });
+        Event::GitHead(path, head.map(|head| head.replace("\r\n", "\n")))
+    })
+    .await
+}
+
+/// The lines of `text` added, changed or following removed lines since
+/// `head`.
+pub fn change_markers(head: &str, text: &str) -> Vec<Marker> {
+    // Both end with a line end, so a missing last one isn't a change
+    let ended = |s: &str| {
+        if s.ends_with('\n') { s.to_string() } else { format!("{s}\n") }
+    };
+    let (head, text) = (ended(head), ended(text));
+    let last = text.lines().count().saturating_sub(1);
+    TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(&head, &text)
+        .ops()
+        .iter()
+        .flat_map(|op| {
+            let (lines, kind) = match *op {
+                DiffOp::Equal { .. } => return Vec::new(),
+                DiffOp::Insert { new_index, new_len, .. } => {
+                    (new_index..new_index + new_len, MarkerKind::GitAdded)
+                }
+                DiffOp::Replace { new_index, new_len, .. } => {
+                    (new_index..new_index + new_len, MarkerKind::GitModified)
+                }
+                DiffOp::Delete { new_index, .. } => {
+                    let line = new_index.min(last);
+                    (line..line + 1, MarkerKind::GitRemoved)
+                }
+            };
+            lines.map(|line| Marker::new(line, kind)).collect()
+        })
+        .collect()
+}
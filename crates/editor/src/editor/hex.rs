@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use iced::{
+    alignment::Vertical,
+    keyboard::{self, key::Named, Key},
+    mouse::ScrollDelta,
+    widget::{
+        button, column, horizontal_space, mouse_area, row, text, text_input,
+        Column, Row,
+    },
+    Element, Length,
+};
+use registers::Event;
+use settings::EditorSettings;
+use ui::labeled_button;
+
+pub const BYTES_PER_ROW: usize = 16;
+/// Rows shown at once; the view is virtualized around `top_row`.
+pub const ROWS: usize = 24;
+
+/// Cursor, scroll and tool inputs of the hex view of a binary file.
+#[derive(Debug, Clone, Default)]
+pub struct HexState {
+    pub cursor: usize,
+    low_nibble: bool,
+    pub top_row: usize,
+    pub goto: String,
+    pub pattern: String,
+    pub status: String,
+}
+
+impl HexState {
+    pub fn move_to(&mut self, offset: usize, len: usize) {
+        self.cursor = offset.min(len.saturating_sub(1));
+        self.low_nibble = false;
+        let row = self.cursor / BYTES_PER_ROW;
+        if row < self.top_row {
+            self.top_row = row;
+        } else if row >= self.top_row + ROWS {
+            self.top_row = row + 1 - ROWS;
+        }
+    }
+
+    pub fn scroll(&mut self, rows: i32, len: usize) {
+        let last = len.div_ceil(BYTES_PER_ROW).saturating_sub(ROWS);
+        self.top_row = self
+            .top_row
+            .saturating_add_signed(rows as isize)
+            .min(last);
+    }
+
+    /// Handles a key press, returning whether a byte was overwritten.
+    pub fn key(
+        &mut self,
+        bytes: &mut [u8],
+        key: Key,
+        modifiers: keyboard::Modifiers,
+    ) -> bool {
+        let len = bytes.len();
+        let page = BYTES_PER_ROW * ROWS;
+        let cursor = self.cursor;
+        match key.as_ref() {
+            Key::Named(Named::ArrowLeft) => {
+                self.move_to(cursor.saturating_sub(1), len)
+            }
+            Key::Named(Named::ArrowRight) => self.move_to(cursor + 1, len),
+            Key::Named(Named::ArrowUp) => {
+                self.move_to(cursor.saturating_sub(BYTES_PER_ROW), len)
+            }
+            Key::Named(Named::ArrowDown) => {
+                self.move_to(cursor + BYTES_PER_ROW, len)
+            }
+            Key::Named(Named::PageUp) => {
+                self.move_to(cursor.saturating_sub(page), len)
+            }
+            Key::Named(Named::PageDown) => self.move_to(cursor + page, len),
+            Key::Named(Named::Home) => {
+                self.move_to(cursor - cursor % BYTES_PER_ROW, len)
+            }
+            Key::Named(Named::End) => self.move_to(
+                cursor - cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+                len,
+            ),
+            Key::Character(c)
+                if !modifiers.control() && !modifiers.alt() && len > 0 =>
+            {
+                let Some(nibble) =
+                    c.chars().next().and_then(|c| c.to_digit(16))
+                else {
+                    return false;
+                };
+                let byte = &mut bytes[cursor];
+                if self.low_nibble {
+                    *byte = (*byte & 0xF0) | nibble as u8;
+                    self.move_to(cursor + 1, len);
+                } else {
+                    *byte = (*byte & 0x0F) | ((nibble as u8) << 4);
+                    self.low_nibble = true;
+                }
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    pub fn goto(&mut self, len: usize) {
+        match parse_offset(&self.goto) {
+            Some(offset) if offset < len => {
+                self.move_to(offset, len);
+                self.status.clear();
+            }
+            _ => self.status = format!("Invalid offset {:?}", self.goto),
+        }
+    }
+
+    pub fn find_next(&mut self, bytes: &[u8]) {
+        let Some(pattern) = parse_pattern(&self.pattern) else {
+            self.status = format!("Invalid pattern {:?}", self.pattern);
+            return;
+        };
+        match find(bytes, &pattern, self.cursor + 1) {
+            Some(offset) => {
+                self.move_to(offset, bytes.len());
+                self.status = format!("Found at 0x{offset:08X}");
+            }
+            None => self.status = "Pattern not found".to_string(),
+        }
+    }
+}
+
+/// Parses `0x1F`, `1Fh` or a decimal offset.
+pub fn parse_offset(input: &str) -> Option<usize> {
+    let input = input.trim();
+    if let Some(hex) = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .or_else(|| input.strip_suffix(['h', 'H']))
+    {
+        usize::from_str_radix(hex, 16).ok()
+    } else {
+        input.parse().ok()
+    }
+}
+
+/// Parses a byte pattern like `DE AD ?? EF` or `deadbeef`, where `??`
+/// matches any byte.
+pub fn parse_pattern(input: &str) -> Option<Vec<Option<u8>>> {
+    let digits: String = input.split_whitespace().collect();
+    if digits.is_empty()
+        || !digits.is_ascii()
+        || !digits.len().is_multiple_of(2)
+    {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| match &digits[i..i + 2] {
+            "??" => Some(None),
+            byte => u8::from_str_radix(byte, 16).ok().map(Some),
+        })
+        .collect()
+}
+
+/// Finds the pattern at or after `from`, wrapping around the end.
+pub fn find(bytes: &[u8], pattern: &[Option<u8>], from: usize) -> Option<usize> {
+    let matches = |start: usize| {
+        pattern.iter().enumerate().all(|(i, p)| {
+            p.map_or(true, |b| bytes.get(start + i) == Some(&b))
+        })
+    };
+    let last = bytes.len().checked_sub(pattern.len())?;
+    let from = from.min(last + 1);
+    (from..=last).chain(0..from).find(|start| matches(*start))
+}
+
+pub fn view(
+    bytes: Arc<Vec<u8>>,
+    state: HexState,
+    metrics: &EditorSettings,
+) -> Element<'static, Event> {
+    let (font, size) = (metrics.font(), metrics.font_size);
+    let rows = bytes.len().div_ceil(BYTES_PER_ROW);
+    let end = (state.top_row + ROWS).min(rows);
+
+    let lines = (state.top_row..end).fold(Column::new(), |lines, line| {
+        let start = line * BYTES_PER_ROW;
+        let chunk = &bytes[start..(start + BYTES_PER_ROW).min(bytes.len())];
+
+        let hex = chunk.iter().enumerate().fold(
+            Row::new().spacing(2),
+            |hex, (i, byte)| {
+                let offset = start + i;
+                hex.push(
+                    button(
+                        text(format!("{byte:02X}")).font(font).size(size),
+                    )
+                    .padding([0, 2])
+                    .style(if offset == state.cursor {
+                        button::primary
+                    } else {
+                        button::text
+                    })
+                    .on_press(Event::HexSelect(offset)),
+                )
+            },
+        );
+        let ascii: String = chunk
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        lines.push(
+            row![
+                text(format!("{start:08X}")).font(font).size(size),
+                hex,
+                text(ascii).font(font).size(size),
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+        )
+    });
+
+    let tools = row![
+        text_input("Offset (0x..)", &state.goto)
+            .on_input(Event::HexGotoInput)
+            .on_submit(Event::HexGoto)
+            .width(Length::Fixed(140.0)),
+        labeled_button("Go", Event::HexGoto),
+        text_input("Bytes (DE AD ?? EF)", &state.pattern)
+            .on_input(Event::HexPatternInput)
+            .on_submit(Event::HexFindNext)
+            .width(Length::Fixed(200.0)),
+        labeled_button("Find", Event::HexFindNext),
+        horizontal_space(),
+        text(state.status.clone()),
+        text(format!(
+            "0x{:08X} / {} bytes",
+            state.cursor,
+            bytes.len()
+        )),
+    ]
+    .spacing(5)
+    .align_y(Vertical::Center)
+    .padding(2);
+
+    column![
+        tools,
+        mouse_area(lines.spacing(1).width(Length::Fill).height(Length::Fill))
+            .on_scroll(|delta| {
+                let rows = match delta {
+                    ScrollDelta::Lines { y, .. } => -y * 3.0,
+                    ScrollDelta::Pixels { y, .. } => -y / 16.0,
+                };
+                Event::HexScroll(rows)
+            }),
+    ]
+    .spacing(4)
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
@@ -0,0 +1,129 @@
+use std::{
+    ops::Range,
+    sync::{Arc, LazyLock},
+};
+
+use iced::{
+    advanced::text::highlighter::{self, Format},
+    Color, Font, Theme,
+};
+use registers::SyntaxTheme;
+use syntect::{
+    highlighting::{self, HighlightState, RangedHighlightIterator, Style},
+    parsing::{ParseState, ScopeStack, SyntaxSet},
+};
+
+static SYNTAXES: LazyLock<SyntaxSet> =
+    LazyLock::new(SyntaxSet::load_defaults_nonewlines);
+
+/// The name of the syntax used for files with the `token` extension.
+pub fn language_name(token: &str) -> Option<&'static str> {
+    SYNTAXES.find_syntax_by_token(token).map(|s| s.name.as_str())
+}
+
+/// Lines between two saved parser states.
+const LINES_PER_SNAPSHOT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub theme: SyntaxTheme,
+    /// The file extension used to pick a syntax.
+    pub token: String,
+    /// [`ui::themes::generation`] when the settings were made.
+    pub generation: usize,
+}
+
+impl Settings {
+    pub fn new(theme: SyntaxTheme, token: String) -> Self {
+        Self {
+            theme,
+            token,
+            generation: ui::themes::generation(),
+        }
+    }
+}
+
+/// A syntax highlighter for the `text_editor` and the minimap, which
+/// unlike iced's own can use the custom themes of the themes directory.
+pub struct SyntaxHighlighter {
+    theme: Arc<highlighting::Theme>,
+    /// The parser state at the start of every `LINES_PER_SNAPSHOT` lines.
+    snapshots: Vec<(ParseState, HighlightState)>,
+    state: (ParseState, HighlightState),
+    current_line: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Highlight(Style);
+
+impl Highlight {
+    pub fn color(&self) -> Option<Color> {
+        let c = self.0.foreground;
+        Some(Color::from_rgba8(c.r, c.g, c.b, c.a as f32 / 255.0))
+    }
+
+    pub fn to_format(&self, _theme: &Theme) -> Format<Font> {
+        Format {
+            color: self.color(),
+            font: None,
+        }
+    }
+}
+
+impl highlighter::Highlighter for SyntaxHighlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        let syntax = SYNTAXES
+            .find_syntax_by_token(&settings.token)
+            .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+        let theme = ui::themes::syntax_theme(&settings.theme);
+        let highlighter = highlighting::Highlighter::new(&theme);
+        let state = (
+            ParseState::new(syntax),
+            HighlightState::new(&highlighter, ScopeStack::new()),
+        );
+        Self {
+            theme,
+            snapshots: vec![state.clone()],
+            state,
+            current_line: 0,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        *self = Self::new(new_settings);
+    }
+
+    fn change_line(&mut self, line: usize) {
+        let snapshot =
+            (line / LINES_PER_SNAPSHOT).min(self.snapshots.len() - 1);
+        self.snapshots.truncate(snapshot + 1);
+        self.state = self.snapshots[snapshot].clone();
+        self.current_line = snapshot * LINES_PER_SNAPSHOT;
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        if self.current_line.is_multiple_of(LINES_PER_SNAPSHOT)
+            && self.current_line / LINES_PER_SNAPSHOT == self.snapshots.len()
+        {
+            self.snapshots.push(self.state.clone());
+        }
+        self.current_line += 1;
+
+        let highlighter = highlighting::Highlighter::new(&self.theme);
+        let (parser, highlight) = &mut self.state;
+        let ops = parser.parse_line(line, &SYNTAXES).unwrap_or_default();
+        RangedHighlightIterator::new(highlight, &ops, line, &highlighter)
+            .filter(|(_, _, range)| !range.is_empty())
+            .map(|(style, _, range)| (range, Highlight(style)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
@@ -0,0 +1,60 @@
+use crate::cursors::Position;
+
+/// The text of a buffer and where its cursor was, before an edit.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub text: String,
+    pub cursor: Position,
+}
+
+impl Snapshot {
+    pub fn new(text: String, cursor: Position) -> Self {
+        Self { text, cursor }
+    }
+}
+
+/// Undo and redo stacks of a file. Consecutive edits of the same kind are
+/// grouped into a single step, like typing a word.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    group: Option<&'static str>,
+}
+
+impl History {
+    const LIMIT: usize = 500;
+
+    /// Records the state before an edit. Passing the same `kind` as the
+    /// previous edit extends the current step instead of starting one.
+    pub fn record(&mut self, before: Snapshot, kind: Option<&'static str>) {
+        self.redo.clear();
+        if kind.is_some() && kind == self.group {
+            return;
+        }
+        self.group = kind;
+        self.undo.push(before);
+        if self.undo.len() > Self::LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Ends the current group, so the next edit starts a new step.
+    pub fn break_group(&mut self) {
+        self.group = None;
+    }
+
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.group = None;
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.group = None;
+        Some(next)
+    }
+}
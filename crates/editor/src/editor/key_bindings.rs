@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::{Mutex, MutexGuard, OnceLock}};
+use std::{collections::HashMap, fmt, sync::{Mutex, MutexGuard, OnceLock}};
 use registers::Event;
 
 #[derive(Debug,Clone)]
@@ -20,6 +20,44 @@ impl KeyBinding {
             event,
         }
     }
+
+    /// Parses a shortcut written the way it is displayed, like
+    /// `Ctrl+Shift+Z`.
+    pub fn parse(shortcut: &str, event: Event) -> Option<Self> {
+        let mut binding = Self::new(' ', false, false, false, event);
+        let mut key = None;
+        for part in shortcut.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "cmd" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => {
+                    let mut chars = part.chars();
+                    let (Some(c), None) = (chars.next(), chars.next()) else {
+                        return None;
+                    };
+                    key = Some(c.to_ascii_lowercase());
+                }
+            }
+        }
+        binding.key = key?;
+        Some(binding)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key.to_ascii_uppercase())
+    }
 }
 
 pub fn get_global_hashmap() -> MutexGuard<'static, HashMap<char, Vec<KeyBinding>>> {
@@ -0,0 +1,373 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use iced::{widget::text_editor, Task};
+use registers::{Event, Register};
+use serde::{Deserialize, Serialize};
+
+use crate::editor_core::EditorCore;
+
+/// Runs of a macro played to the end of the file, in case it never gets
+/// there.
+const MAX_RUNS: usize = 10_000;
+/// Runs played before the editor gets to draw and handle input again.
+const RUNS_PER_CHUNK: usize = 100;
+
+/// A `text_editor::Motion` that can be written to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MacroMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    DocumentStart,
+    DocumentEnd,
+}
+
+impl From<text_editor::Motion> for MacroMotion {
+    fn from(motion: text_editor::Motion) -> Self {
+        use text_editor::Motion;
+        match motion {
+            Motion::Left => Self::Left,
+            Motion::Right => Self::Right,
+            Motion::Up => Self::Up,
+            Motion::Down => Self::Down,
+            Motion::WordLeft => Self::WordLeft,
+            Motion::WordRight => Self::WordRight,
+            Motion::Home => Self::Home,
+            Motion::End => Self::End,
+            Motion::PageUp => Self::PageUp,
+            Motion::PageDown => Self::PageDown,
+            Motion::DocumentStart => Self::DocumentStart,
+            Motion::DocumentEnd => Self::DocumentEnd,
+        }
+    }
+}
+
+impl From<MacroMotion> for text_editor::Motion {
+    fn from(motion: MacroMotion) -> Self {
+        match motion {
+            MacroMotion::Left => Self::Left,
+            MacroMotion::Right => Self::Right,
+            MacroMotion::Up => Self::Up,
+            MacroMotion::Down => Self::Down,
+            MacroMotion::WordLeft => Self::WordLeft,
+            MacroMotion::WordRight => Self::WordRight,
+            MacroMotion::Home => Self::Home,
+            MacroMotion::End => Self::End,
+            MacroMotion::PageUp => Self::PageUp,
+            MacroMotion::PageDown => Self::PageDown,
+            MacroMotion::DocumentStart => Self::DocumentStart,
+            MacroMotion::DocumentEnd => Self::DocumentEnd,
+        }
+    }
+}
+
+/// A recorded editor action or command. Clicks and scrolling depend on
+/// the layout, so they aren't recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MacroStep {
+    Insert(char),
+    Paste(String),
+    Enter,
+    Backspace,
+    Delete,
+    Move(MacroMotion),
+    Select(MacroMotion),
+    SelectWord,
+    SelectLine,
+    SelectAll,
+    Undo,
+    Redo,
+    AddCursorAbove,
+    AddCursorBelow,
+    AddNextOccurrence,
+    SelectAllOccurrences,
+    ClearCursors,
+    ToggleLineEndings,
+    RunScript(String),
+    Copy,
+    Cut,
+    CopyToRegister(char),
+    PasteRegister(char),
+}
+
+impl MacroStep {
+    /// The step recorded for `event`, if it is recorded.
+    pub fn of(event: &Event) -> Option<Self> {
+        use text_editor::{Action, Edit};
+        Some(match event {
+            Event::EditorAction(action) => match action {
+                Action::Edit(Edit::Insert(c)) => Self::Insert(*c),
+                Action::Edit(Edit::Paste(text)) => Self::Paste(text.to_string()),
+                Action::Edit(Edit::Enter) => Self::Enter,
+                Action::Edit(Edit::Backspace) => Self::Backspace,
+                Action::Edit(Edit::Delete) => Self::Delete,
+                Action::Move(motion) => Self::Move((*motion).into()),
+                Action::Select(motion) => Self::Select((*motion).into()),
+                Action::SelectWord => Self::SelectWord,
+                Action::SelectLine => Self::SelectLine,
+                Action::SelectAll => Self::SelectAll,
+                _ => return None,
+            },
+            Event::InsertText(text) => Self::Paste(text.clone()),
+            Event::Undo => Self::Undo,
+            Event::Redo => Self::Redo,
+            Event::AddCursorAbove => Self::AddCursorAbove,
+            Event::AddCursorBelow => Self::AddCursorBelow,
+            Event::AddNextOccurrence => Self::AddNextOccurrence,
+            Event::SelectAllOccurrences => Self::SelectAllOccurrences,
+            Event::ClearCursors => Self::ClearCursors,
+            Event::ToggleLineEndings => Self::ToggleLineEndings,
+            Event::RunScript(function) => Self::RunScript(function.clone()),
+            Event::Copy => Self::Copy,
+            Event::Cut => Self::Cut,
+            Event::CopyToRegister(name) => Self::CopyToRegister(*name),
+            Event::PasteRegister(name) => Self::PasteRegister(*name),
+            _ => return None,
+        })
+    }
+
+    pub fn event(&self) -> Event {
+        use text_editor::{Action, Edit};
+        let action = match self {
+            Self::Insert(c) => Action::Edit(Edit::Insert(*c)),
+            Self::Paste(text) => Action::Edit(Edit::Paste(Arc::new(text.clone()))),
+            Self::Enter => Action::Edit(Edit::Enter),
+            Self::Backspace => Action::Edit(Edit::Backspace),
+            Self::Delete => Action::Edit(Edit::Delete),
+            Self::Move(motion) => Action::Move((*motion).into()),
+            Self::Select(motion) => Action::Select((*motion).into()),
+            Self::SelectWord => Action::SelectWord,
+            Self::SelectLine => Action::SelectLine,
+            Self::SelectAll => Action::SelectAll,
+            Self::Undo => return Event::Undo,
+            Self::Redo => return Event::Redo,
+            Self::AddCursorAbove => return Event::AddCursorAbove,
+            Self::AddCursorBelow => return Event::AddCursorBelow,
+            Self::AddNextOccurrence => return Event::AddNextOccurrence,
+            Self::SelectAllOccurrences => return Event::SelectAllOccurrences,
+            Self::ClearCursors => return Event::ClearCursors,
+            Self::ToggleLineEndings => return Event::ToggleLineEndings,
+            Self::RunScript(function) => return Event::RunScript(function.clone()),
+            Self::Copy => return Event::Copy,
+            Self::Cut => return Event::Cut,
+            Self::CopyToRegister(name) => return Event::CopyToRegister(*name),
+            Self::PasteRegister(name) => return Event::PasteRegister(*name),
+        };
+        Event::EditorAction(action)
+    }
+}
+
+/// A saved macro.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    /// A shortcut playing it once, like `Ctrl+Alt+1`.
+    #[serde(default)]
+    pub key: Option<String>,
+    pub steps: Vec<MacroStep>,
+}
+
+/// The saved macros, as written in the macros file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct MacroFile {
+    macros: BTreeMap<String, Macro>,
+    /// The macro recorded last.
+    last: Option<String>,
+}
+
+/// A macro being played, a chunk of runs at a time.
+#[derive(Debug, Clone)]
+struct Playback {
+    /// The id of the pane it is played in.
+    pane: usize,
+    steps: Vec<MacroStep>,
+    runs_left: usize,
+    /// Whether it stops at the end of the file rather than after a count.
+    to_end: bool,
+}
+
+/// The saved macros, the one being recorded and the one being played.
+#[derive(Debug, Clone, Default)]
+pub struct Macros {
+    file: MacroFile,
+    recording: Option<(String, Vec<MacroStep>)>,
+    playback: Option<Playback>,
+    /// The macro waiting for a shortcut to be pressed.
+    binding: Option<String>,
+}
+
+impl Macros {
+    pub fn path() -> Option<PathBuf> {
+        Some(registers::paths::data_dir()?.join("macros.json"))
+    }
+
+    pub fn load() -> Self {
+        let Some(content) =
+            Self::path().and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Self::default();
+        };
+        match serde_json::from_str(&content) {
+            Ok(file) => Self {
+                file,
+                ..Self::default()
+            },
+            Err(e) => {
+                println!("Ignoring invalid macros file: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    fn store(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(&self.file)
+                    .map_err(std::io::Error::other)?;
+                std::fs::write(&path, json)
+            });
+        if let Err(e) = written {
+            println!("Unable to write macros {:?}: {e}", path);
+        }
+    }
+
+    /// The name of the macro being recorded.
+    pub fn recording(&self) -> Option<&str> {
+        self.recording.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Starts recording a macro, named `macro-N` without a name, or stops
+    /// and saves the one being recorded.
+    pub fn toggle(&mut self, name: Option<String>) {
+        match self.recording.take() {
+            Some((name, steps)) => {
+                if steps.is_empty() {
+                    return;
+                }
+                let key = self.file.macros.get(&name).and_then(|m| m.key.clone());
+                self.file.macros.insert(name.clone(), Macro { key, steps });
+                self.file.last = Some(name);
+                self.store();
+            }
+            None => {
+                let name = name.unwrap_or_else(|| {
+                    (1..)
+                        .map(|n| format!("macro-{n}"))
+                        .find(|n| !self.file.macros.contains_key(n))
+                        .unwrap_or_default()
+                });
+                self.recording = Some((name, Vec::new()));
+            }
+        }
+    }
+
+    /// Adds `event` to the macro being recorded, when it is recorded.
+    pub fn record(&mut self, event: &Event) {
+        if let Some((_, steps)) = &mut self.recording {
+            steps.extend(MacroStep::of(event));
+        }
+    }
+
+    /// The named macro if saved, or the last one recorded.
+    fn resolve(&self, name: Option<&str>) -> Option<&str> {
+        let name = name.or(self.file.last.as_deref())?;
+        self.file.macros.get_key_value(name).map(|(name, _)| name.as_str())
+    }
+
+    /// The steps of the named macro, or of the last one recorded.
+    pub fn steps(&self, name: Option<&str>) -> Option<&[MacroStep]> {
+        let name = self.resolve(name)?;
+        self.file.macros.get(name).map(|m| m.steps.as_slice())
+    }
+
+    /// Starts playing the named macro, or the last one recorded, in the
+    /// pane with the id `pane`, `times` times or to the end of the file.
+    pub fn play(&mut self, name: Option<&str>, pane: usize, times: Option<usize>) {
+        self.playback = self.steps(name).map(|steps| Playback {
+            pane,
+            steps: steps.to_vec(),
+            runs_left: times.unwrap_or(MAX_RUNS),
+            to_end: times.is_none(),
+        });
+    }
+
+    /// The id of the pane a macro is being played in.
+    pub fn playing_in(&self) -> Option<usize> {
+        self.playback.as_ref().map(|playback| playback.pane)
+    }
+
+    pub fn stop(&mut self) {
+        self.playback = None;
+    }
+
+    /// Plays the next chunk of runs of the macro being played on `core`.
+    /// Returns whether it is done.
+    pub fn resume(&mut self, core: &mut EditorCore) -> (Task<Event>, bool) {
+        let Some(playback) = &mut self.playback else {
+            return (Task::none(), true);
+        };
+        let mut tasks = Vec::new();
+        let mut done = false;
+        for _ in 0..RUNS_PER_CHUNK.min(playback.runs_left) {
+            playback.runs_left -= 1;
+            let before = core.cursor_position();
+            for step in &playback.steps {
+                tasks.push(core.update(step.event()));
+            }
+            let after = core.cursor_position();
+            if playback.to_end
+                && (before.0 + 1 >= core.line_count() || after <= before)
+            {
+                done = true;
+                break;
+            }
+        }
+        done |= playback.runs_left == 0;
+        if done {
+            self.playback = None;
+        }
+        (Task::batch(tasks), done)
+    }
+
+    /// Waits for a shortcut to bind to the named macro, or to the last one
+    /// recorded, or stops waiting.
+    pub fn bind_next(&mut self, name: Option<&str>) {
+        self.binding = match self.binding {
+            Some(_) => None,
+            None => self.resolve(name).map(String::from),
+        };
+    }
+
+    /// The macro waiting for a shortcut.
+    pub fn binding(&self) -> Option<&str> {
+        self.binding.as_deref()
+    }
+
+    /// Saves `key` as the shortcut of the macro waiting for one. Returns
+    /// the name of the macro.
+    pub fn set_key(&mut self, key: String) -> Option<String> {
+        let name = self.binding.take()?;
+        self.file.macros.get_mut(&name)?.key = Some(key);
+        self.store();
+        Some(name)
+    }
+
+    /// The shortcut and name of every macro with a key.
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.file.macros.iter().filter_map(|(name, m)| {
+            m.key.as_deref().map(|key| (key, name.as_str()))
+        })
+    }
+}
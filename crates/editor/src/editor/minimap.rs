@@ -0,0 +1,331 @@
+use std::{
+    cell::{Cell, RefCell},
+    ops::Range,
+};
+
+use iced::{
+    advanced::text::Highlighter as _,
+    mouse,
+    widget::{
+        canvas::{self, event, Cache, Frame, Geometry},
+        text_editor,
+    },
+    Color, Point, Rectangle, Renderer, Size, Theme,
+};
+use registers::{Event, Marker, MarkerKind, SyntaxTheme};
+
+use crate::highlighter::{Settings, SyntaxHighlighter};
+
+/// Height of a source line in the minimap, when the file is small enough.
+const MAX_ROW_HEIGHT: f32 = 3.0;
+/// Columns represented along the minimap width.
+const COLUMNS: f32 = 120.0;
+/// Width of the marker strip on the right edge.
+const MARKER_WIDTH: f32 = 4.0;
+
+/// A scaled-down, syntax coloured view of a whole buffer, drawn next to
+/// the `text_editor` of an [`EditorCore`](crate::editor_core::EditorCore).
+pub struct Minimap<'a> {
+    content: &'a text_editor::Content,
+    markers: Vec<Marker>,
+    theme: SyntaxTheme,
+    token: String,
+    first_line: usize,
+    line_height: f32,
+    revision: usize,
+}
+
+#[derive(Default)]
+pub struct State {
+    cache: Cache,
+    revision: Cell<Option<usize>>,
+    highlights: RefCell<Highlights>,
+    dragging: bool,
+}
+
+/// The coloured ranges of each line drawn, kept between revisions so only
+/// the lines from the first edited one are highlighted again.
+#[derive(Default)]
+struct Highlights {
+    settings: Option<Settings>,
+    highlighter: Option<SyntaxHighlighter>,
+    lines: Vec<(String, Vec<(Range<usize>, Color)>)>,
+}
+
+impl Highlights {
+    /// Highlights `lines`, starting from the parser state saved before the
+    /// first line that changed.
+    fn refresh(&mut self, settings: &Settings, lines: Vec<String>) {
+        if self.settings.as_ref() != Some(settings) {
+            *self = Self {
+                settings: Some(settings.clone()),
+                ..Self::default()
+            };
+        }
+        let unchanged = self.lines.iter()
+            .zip(&lines)
+            .take_while(|((old, _), new)| old == *new)
+            .count();
+        if unchanged == lines.len() && unchanged == self.lines.len() {
+            return;
+        }
+        let highlighter = self.highlighter
+            .get_or_insert_with(|| SyntaxHighlighter::new(settings));
+        highlighter.change_line(unchanged);
+        let start = highlighter.current_line().min(self.lines.len());
+        self.lines.truncate(start);
+        for line in lines.into_iter().skip(start) {
+            let colors = highlighter.highlight_line(&line)
+                .filter_map(|(range, highlight)| {
+                    Some((range, highlight.color()?))
+                })
+                .collect();
+            self.lines.push((line, colors));
+        }
+    }
+}
+
+impl<'a> Minimap<'a> {
+    pub fn new(
+        content: &'a text_editor::Content,
+        markers: Vec<Marker>,
+        theme: SyntaxTheme,
+        token: String,
+        first_line: usize,
+        line_height: f32,
+        revision: usize,
+    ) -> Self {
+        Self {
+            content,
+            markers,
+            theme,
+            token,
+            first_line,
+            line_height,
+            revision,
+        }
+    }
+
+    fn row_height(&self, bounds: Rectangle) -> f32 {
+        let lines = self.content.line_count().max(1) as f32;
+        (bounds.height / lines).min(MAX_ROW_HEIGHT)
+    }
+
+    fn visible_lines(&self, bounds: Rectangle) -> usize {
+        ((bounds.height / self.line_height) as usize).max(1)
+    }
+
+    /// The first visible line, following the cursor when it leaves the
+    /// tracked scroll position (as the `text_editor` does).
+    fn viewport_start(&self, bounds: Rectangle) -> usize {
+        let visible = self.visible_lines(bounds);
+        let (cursor, _) = self.content.cursor_position();
+        if cursor < self.first_line {
+            cursor
+        } else if cursor >= self.first_line + visible {
+            cursor + 1 - visible
+        } else {
+            self.first_line
+        }
+    }
+
+    fn scroll_to(&self, y: f32, bounds: Rectangle) -> Event {
+        let last = self.content.line_count().saturating_sub(1);
+        let line = ((y.max(0.0) / self.row_height(bounds)) as usize).min(last);
+        let target = line.saturating_sub(self.visible_lines(bounds) / 2);
+        Event::MinimapScroll(self.viewport_start(bounds), target)
+    }
+
+    fn draw_lines(
+        &self,
+        frame: &mut Frame,
+        theme: &Theme,
+        highlights: &mut Highlights,
+    ) {
+        let palette = theme.extended_palette();
+        let row = self.row_height(Rectangle::with_size(frame.size()));
+        let column = frame.width() / COLUMNS;
+        let block = (row - 1.0).max(1.0).min(row);
+        let default = Color {
+            a: 0.6,
+            ..palette.background.base.text
+        };
+
+        frame.fill_rectangle(
+            Point::ORIGIN,
+            frame.size(),
+            palette.background.weak.color,
+        );
+
+        // Only the lines that fit are drawn, so only those are highlighted
+        let rows = (frame.height() / row) as usize + 1;
+        let lines = self.content.lines()
+            .take(rows)
+            .map(|line| String::from(&*line))
+            .collect();
+        highlights.refresh(
+            &Settings::new(self.theme.clone(), self.token.clone()),
+            lines,
+        );
+
+        for (i, (line, colors)) in highlights.lines.iter().enumerate() {
+            let y = i as f32 * row;
+            if y > frame.height() {
+                break;
+            }
+
+            let mut fill = |range: Range<usize>, color: Color| {
+                for (start, end) in runs(&line.as_bytes()[range.clone()]) {
+                    frame.fill_rectangle(
+                        Point::new((range.start + start) as f32 * column, y),
+                        Size::new((end - start) as f32 * column, block),
+                        color,
+                    );
+                }
+            };
+
+            fill(0..line.len(), default);
+            for (range, color) in colors {
+                fill(range.clone(), Color { a: 0.8, ..*color });
+            }
+        }
+    }
+
+    fn marker_color(kind: MarkerKind, theme: &Theme) -> Color {
+        let palette = theme.extended_palette();
+        match kind {
+            MarkerKind::SearchHit => palette.primary.strong.color,
+            MarkerKind::Error => palette.danger.strong.color,
+            MarkerKind::Warning => iced::color!(0xe5c07b),
+            MarkerKind::GitAdded => palette.success.base.color,
+            MarkerKind::GitModified => palette.primary.weak.color,
+            MarkerKind::GitRemoved => palette.danger.weak.color,
+        }
+    }
+}
+
+impl canvas::Program<Event> for Minimap<'_> {
+    type State = State;
+
+    fn update(
+        &self,
+        state: &mut State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Event>) {
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(
+                mouse::Button::Left,
+            )) => {
+                if let Some(position) = cursor.position_in(bounds) {
+                    state.dragging = true;
+                    return (
+                        event::Status::Captured,
+                        Some(self.scroll_to(position.y, bounds)),
+                    );
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { position })
+                if state.dragging =>
+            {
+                return (
+                    event::Status::Captured,
+                    Some(self.scroll_to(position.y - bounds.y, bounds)),
+                );
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(
+                mouse::Button::Left,
+            )) if state.dragging => {
+                state.dragging = false;
+                return (event::Status::Captured, None);
+            }
+            _ => {}
+        }
+        (event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        state: &State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        if state.revision.get() != Some(self.revision) {
+            state.cache.clear();
+            state.revision.set(Some(self.revision));
+        }
+
+        let lines = state.cache.draw(renderer, bounds.size(), |frame| {
+            let mut highlights = state.highlights.borrow_mut();
+            self.draw_lines(frame, theme, &mut highlights);
+        });
+
+        let mut overlay = Frame::new(renderer, bounds.size());
+        let row = self.row_height(bounds);
+        let palette = theme.extended_palette();
+
+        overlay.fill_rectangle(
+            Point::new(0.0, self.viewport_start(bounds) as f32 * row),
+            Size::new(
+                bounds.width,
+                (self.visible_lines(bounds) as f32 * row).min(bounds.height),
+            ),
+            Color {
+                a: 0.25,
+                ..palette.secondary.base.color
+            },
+        );
+
+        for marker in &self.markers {
+            overlay.fill_rectangle(
+                Point::new(
+                    bounds.width - MARKER_WIDTH,
+                    marker.line as f32 * row,
+                ),
+                Size::new(MARKER_WIDTH, row.max(2.0)),
+                Self::marker_color(marker.kind, theme),
+            );
+        }
+
+        vec![lines, overlay.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.dragging {
+            mouse::Interaction::Grabbing
+        } else if cursor.is_over(bounds) {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Byte ranges of the non-whitespace runs in a line. Bytes are used as
+/// columns, which is close enough at minimap scale.
+fn runs(line: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, b) in line.iter().enumerate() {
+        match (start, b.is_ascii_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                runs.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push((s, line.len()));
+    }
+    runs
+}
@@ -1,8 +1,30 @@
+pub mod autosave;
+pub mod clipboard_panel;
+pub mod cursors;
+pub mod file_browser;
 pub mod fileinfo;
+pub mod git;
+pub use registers::filesystem;
+pub mod hex;
+pub mod highlighter;
+pub mod history;
 pub mod services;
+#[allow(clippy::module_inception)]
 pub mod editor;
 pub mod editor_core;
 pub mod pane;
+pub mod picker;
+pub mod preview;
+pub mod quick_open;
+pub mod recovery;
+pub mod session;
+pub mod status;
+pub mod swap;
 pub mod key_bindings;
+pub mod macros;
+pub mod viewer;
+pub mod welcome;
+pub mod minimap;
+pub mod zoom;
 
 pub use editor::*;
\ No newline at end of file
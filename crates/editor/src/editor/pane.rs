@@ -1,6 +1,6 @@
-use iced::Color;
+use iced::{widget::pane_grid, Color};
 
-use super::editor_core::EditorCore;
+use super::{editor_core::EditorCore, preview::Preview};
 
 pub const PANE_ID_COLOR_UNFOCUSED: Color = Color::from_rgb(
     0xFF as f32 / 255.0,
@@ -18,6 +18,7 @@ pub struct Pane {
     pub id: usize,
     pub core: EditorCore,
     pub is_pinned: bool,
+    pub preview: Option<Preview>,
 }
 
 impl Pane {
@@ -26,6 +27,14 @@ impl Pane {
             id,
             core: EditorCore::new(),
             is_pinned: false,
+            preview: None,
+        }
+    }
+    
+    pub fn preview(id: usize, source: pane_grid::Pane) -> Self {
+        Self {
+            preview: Some(Preview::new(source)),
+            ..Self::new(id)
         }
     }
 }
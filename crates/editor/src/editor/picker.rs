@@ -0,0 +1,49 @@
+use std::{fmt, path::PathBuf};
+
+use iced::futures::{future::BoxFuture, FutureExt};
+
+/// Asks the user for a file or folder. Held by every [`EditorCore`] so the
+/// native dialogs can be replaced, like in tests or by the in-app browser.
+/// The answer is awaited, so asking doesn't block the services.
+///
+/// [`EditorCore`]: crate::editor_core::EditorCore
+pub trait FilePicker: fmt::Debug + Send + Sync {
+    fn pick_file(&self) -> BoxFuture<'static, Option<PathBuf>>;
+
+    fn pick_folder(&self) -> BoxFuture<'static, Option<PathBuf>>;
+
+    /// Where to write a new file, suggesting `name`.
+    fn save_file(&self, name: &str) -> BoxFuture<'static, Option<PathBuf>>;
+}
+
+/// The dialogs of the system, starting where the user last opened
+/// something.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativePicker;
+
+impl NativePicker {
+    fn dialog() -> rfd::AsyncFileDialog {
+        let dialog = rfd::AsyncFileDialog::new();
+        match settings::recent::start_dir() {
+            Some(dir) => dialog.set_directory(dir),
+            None => dialog,
+        }
+    }
+}
+
+impl FilePicker for NativePicker {
+    fn pick_file(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        let picked = Self::dialog().pick_file();
+        async move { Some(picked.await?.path().to_path_buf()) }.boxed()
+    }
+
+    fn pick_folder(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        let picked = Self::dialog().pick_folder();
+        async move { Some(picked.await?.path().to_path_buf()) }.boxed()
+    }
+
+    fn save_file(&self, name: &str) -> BoxFuture<'static, Option<PathBuf>> {
+        let picked = rfd::AsyncFileDialog::new().set_file_name(name).save_file();
+        async move { Some(picked.await?.path().to_path_buf()) }.boxed()
+    }
+}
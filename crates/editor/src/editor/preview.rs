@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use iced::{
+    widget::{container, markdown, scrollable},
+    Element, Length, Task, Theme,
+};
+use registers::Event;
+
+use crate::editor_core::EditorCore;
+
+/// A rendered view of the Markdown buffer of another pane, kept in sync
+/// with its text and scroll position.
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub source: iced::widget::pane_grid::Pane,
+    items: Vec<markdown::Item>,
+    scroll_id: scrollable::Id,
+    revision: Option<usize>,
+    position: f32,
+    dark: bool,
+}
+
+impl Preview {
+    pub fn new(source: iced::widget::pane_grid::Pane) -> Self {
+        Self {
+            source,
+            items: Vec::new(),
+            scroll_id: scrollable::Id::unique(),
+            revision: None,
+            position: 0.0,
+            dark: true,
+        }
+    }
+
+    pub fn is_markdown(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                matches!(ext.to_lowercase().as_str(), "md" | "markdown")
+            })
+    }
+
+    /// Reparses the source buffer when it changed and follows its scroll.
+    pub fn sync(&mut self, core: &EditorCore) -> Task<Event> {
+        if self.revision != Some(core.revision()) {
+            self.items = markdown::parse(&core.get_content()).collect();
+            self.revision = Some(core.revision());
+        }
+        self.dark = ui::themes::is_dark(&core.theme);
+
+        let lines = core.line_count().saturating_sub(1).max(1);
+        let position = (core.scroll_line() as f32 / lines as f32).min(1.0);
+        if position == self.position {
+            return Task::none();
+        }
+        self.position = position;
+        scrollable::snap_to(
+            self.scroll_id.clone(),
+            scrollable::RelativeOffset { x: 0.0, y: position },
+        )
+    }
+
+    pub fn view(&self) -> Element<'_, Event> {
+        let palette = if self.dark {
+            Theme::Dark.palette()
+        } else {
+            Theme::Light.palette()
+        };
+
+        let rendered = markdown::view(
+            &self.items,
+            markdown::Settings::default(),
+            markdown::Style::from_palette(palette),
+        )
+        .map(|url| Event::OpenLink(url.to_string()));
+
+        container(
+            scrollable(container(rendered).padding(10))
+                .id(self.scroll_id.clone())
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(4)
+        .into()
+    }
+}
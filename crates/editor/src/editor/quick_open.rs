@@ -0,0 +1,255 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, container, row, scrollable, text, text_input, Column},
+    Element, Length, Task,
+};
+use registers::{Dock, Event, Register};
+use settings::recent;
+use ui::labeled_button;
+
+use crate::filesystem::FileSystem;
+
+/// Entries listed at once.
+const MAX_RESULTS: usize = 50;
+/// Files listed from the workspace folder.
+const MAX_FILES: usize = 5_000;
+const MAX_DEPTH: usize = 6;
+/// Folders of the workspace that are never listed.
+const SKIPPED_DIRS: [&str; 3] = ["target", "node_modules", "dist"];
+
+fn input_id() -> text_input::Id {
+    text_input::Id::new("quick-open")
+}
+
+/// Finds a recent file or folder, or a file of the workspace folder, by a
+/// part of its path.
+pub struct QuickOpen {
+    visible: bool,
+    input: String,
+    workspace: Option<PathBuf>,
+    /// The files of the workspace folder, listed in the background the
+    /// first time they are needed.
+    files: Vec<PathBuf>,
+    /// The folder `files` were, or are being, listed from.
+    listed: Option<PathBuf>,
+    fs: Arc<dyn FileSystem>,
+}
+
+/// An entry of the list, with what a click sends and whether it can be
+/// forgotten.
+struct Match {
+    path: PathBuf,
+    detail: String,
+    event: Event,
+    recent: bool,
+}
+
+impl QuickOpen {
+    /// Lists the workspace folder from `fs`.
+    pub fn new(fs: Arc<dyn FileSystem>) -> Self {
+        Self {
+            visible: false,
+            input: String::new(),
+            workspace: recent::workspace(),
+            files: Vec::new(),
+            listed: None,
+            fs,
+        }
+    }
+
+    /// Lists the files of the workspace folder away from the UI thread,
+    /// unless they already are.
+    fn list_workspace(&mut self) -> Task<Event> {
+        if self.listed == self.workspace {
+            return Task::none();
+        }
+        self.files.clear();
+        self.listed = self.workspace.clone();
+        let Some(folder) = self.workspace.clone() else {
+            return Task::none();
+        };
+        let fs = self.fs.clone();
+        Task::perform(
+            smol::unblock(move || {
+                let mut files = Vec::new();
+                list_files(fs.as_ref(), &folder, 0, &mut files);
+                (folder, files)
+            }),
+            |(folder, files)| Event::WorkspaceListed(folder, files),
+        )
+    }
+
+    fn matches(&self) -> Vec<Match> {
+        let input = self.input.to_lowercase();
+        let found = |path: &Path| {
+            path.to_string_lossy().to_lowercase().contains(&input)
+        };
+        let recent = recent::current();
+        let files = recent.files.iter().map(|e| Match {
+            path: e.path.clone(),
+            detail: e.age(),
+            event: Event::OpenPath(e.path.clone()),
+            recent: true,
+        });
+        let folders = recent.folders.iter().map(|e| Match {
+            path: e.path.clone(),
+            detail: format!("folder, {}", e.age()),
+            event: Event::FolderOpened(e.path.clone()),
+            recent: true,
+        });
+        let workspace = self.files.iter()
+            .filter(|path| !recent.files.iter().any(|e| e.path == **path))
+            .map(|path| Match {
+                path: path.clone(),
+                detail: String::new(),
+                event: Event::OpenPath(path.clone()),
+                recent: false,
+            });
+        files.chain(folders)
+            .chain(workspace)
+            .filter(|m| found(&m.path))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+}
+
+/// Collects the files under `dir` in `fs`, skipping hidden and build folders.
+fn list_files(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    depth: usize,
+    files: &mut Vec<PathBuf>,
+) {
+    let Ok(paths) = fs.list_dir(dir) else {
+        return;
+    };
+    for path in paths {
+        if files.len() >= MAX_FILES {
+            return;
+        }
+        let name = path.file_name()
+            .map_or(String::new(), |n| n.to_string_lossy().to_string());
+        if name.starts_with('.') {
+            continue;
+        }
+        if fs.is_dir(&path) {
+            if depth < MAX_DEPTH && !SKIPPED_DIRS.contains(&name.as_str()) {
+                list_files(fs, &path, depth + 1, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+impl Register for QuickOpen {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        match event {
+            Event::ToggleQuickOpen => {
+                self.visible = !self.visible;
+                self.input.clear();
+                if self.visible {
+                    Task::batch([text_input::focus(input_id()), self.list_workspace()])
+                } else {
+                    Task::none()
+                }
+            }
+            Event::QuickOpenInput(input) => {
+                self.input = input;
+                Task::none()
+            }
+            Event::QuickOpenSubmit => match self.matches().into_iter().next() {
+                Some(first) => Task::done(first.event),
+                None => Task::none(),
+            },
+            Event::FolderOpened(folder) => {
+                self.workspace = Some(folder);
+                self.visible = true;
+                self.input.clear();
+                Task::batch([text_input::focus(input_id()), self.list_workspace()])
+            }
+            Event::WorkspaceListed(folder, files) => {
+                if self.listed.as_ref() == Some(&folder) {
+                    self.files = files;
+                }
+                Task::none()
+            }
+            Event::OpenPath(_) => {
+                self.visible = false;
+                Task::none()
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        if !self.visible {
+            return Column::new().into();
+        }
+        let settings = settings::current();
+
+        let base = self.workspace.as_deref();
+        let entries = self.matches().into_iter().fold(
+            Column::new().spacing(2),
+            |entries, m| {
+                let shown = base
+                    .and_then(|base| m.path.strip_prefix(base).ok())
+                    .unwrap_or(m.path.as_path())
+                    .display()
+                    .to_string();
+                let open = button(
+                    row![
+                        text(shown),
+                        text(m.detail).size(12).color(iced::color!(0xc2c2c2)),
+                    ]
+                    .spacing(10)
+                    .align_y(Vertical::Center),
+                )
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(m.event);
+                let forget = m.recent.then(|| {
+                    button(text("×"))
+                        .style(button::text)
+                        .on_press(Event::RemoveRecent(m.path.clone()))
+                });
+                entries.push(
+                    row![open].push_maybe(forget).align_y(Vertical::Center),
+                )
+            },
+        );
+
+        let header = row![
+            text("Open"),
+            text_input("Recent or workspace file", &self.input)
+                .id(input_id())
+                .on_input(Event::QuickOpenInput)
+                .on_submit(Event::QuickOpenSubmit),
+            labeled_button("Close", Event::ToggleQuickOpen),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        container(
+            column![header, scrollable(entries).height(Length::Fixed(300.0))]
+                .spacing(settings.ui.spacing),
+        )
+        .style(container::bordered_box)
+        .padding(settings.ui.padding)
+        .width(Length::Fill)
+        .into()
+    }
+
+    fn title(&self) -> String {
+        String::from("Quick Open")
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Top
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use chrono::DateTime;
+use iced::{
+    alignment::Vertical,
+    widget::{column, container, horizontal_space, row, scrollable, text, Column},
+    Color, Element, Font, Length, Task,
+};
+use registers::{Dock, Event, Register};
+use similar::{ChangeTag, TextDiff};
+use ui::{danger_button, labeled_button};
+
+use crate::filesystem::FileSystem;
+use crate::swap::{self, Swap};
+
+/// Offers to restore the unsaved buffers found in the recovery directory
+/// at startup, or to compare them with the files on disk.
+pub struct Recovery {
+    swaps: Vec<Swap>,
+    diff: Option<(usize, Vec<(ChangeTag, String)>)>,
+    fs: Arc<dyn FileSystem>,
+}
+
+impl Recovery {
+    /// Finds the swap files in `fs`, where the files they are compared
+    /// with are read too.
+    pub fn new(fs: Arc<dyn FileSystem>) -> Self {
+        Self {
+            swaps: swap::pending(fs.as_ref()),
+            diff: None,
+            fs,
+        }
+    }
+
+    fn name(swap: &Swap) -> String {
+        swap.path
+            .file_name()
+            .map_or_else(|| swap.path.display().to_string(), |n| {
+                n.to_string_lossy().to_string()
+            })
+    }
+
+    fn diff(&self, swap: &Swap) -> Vec<(ChangeTag, String)> {
+        let on_disk = if swap.untitled {
+            String::new()
+        } else {
+            self.fs.read_to_string(&swap.path).unwrap_or_default()
+        };
+        TextDiff::from_lines(&on_disk, &swap.content)
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Equal)
+            .map(|change| (change.tag(), change.to_string()))
+            .collect()
+    }
+
+    fn take(&mut self, idx: usize) -> Option<Swap> {
+        if idx >= self.swaps.len() {
+            return None;
+        }
+        self.diff = None;
+        let swap = self.swaps.remove(idx);
+        swap::discard(self.fs.as_ref(), &swap);
+        Some(swap)
+    }
+}
+
+impl Register for Recovery {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        match event {
+            Event::RecoveryRestore(idx) => match self.take(idx) {
+                Some(swap) => Task::done(Event::Recovered(
+                    swap.path,
+                    swap.content,
+                    swap.untitled,
+                )),
+                None => Task::none(),
+            },
+            Event::RecoveryDiscard(idx) => {
+                self.take(idx);
+                Task::none()
+            }
+            Event::RecoveryDiff(idx) => {
+                self.diff = match self.diff {
+                    Some((shown, _)) if shown == idx => None,
+                    _ => self.swaps.get(idx).map(|s| (idx, self.diff(s))),
+                };
+                Task::none()
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        if self.swaps.is_empty() {
+            return Column::new().into();
+        }
+        let settings = settings::current();
+
+        let entries = self.swaps.iter().enumerate().fold(
+            Column::new().spacing(4),
+            |entries, (idx, swap)| {
+                let written = DateTime::from_timestamp(swap.written, 0)
+                    .map(|t| t.with_timezone(&chrono::Local))
+                    .map_or(String::new(), |t| {
+                        t.format("%v %r").to_string()
+                    });
+                entries.push(
+                    row![
+                        text(Self::name(swap)),
+                        text(written).color(iced::color!(0xc2c2c2)),
+                        horizontal_space(),
+                        labeled_button("Restore", Event::RecoveryRestore(idx)),
+                        labeled_button("Diff", Event::RecoveryDiff(idx)),
+                        danger_button(
+                            text("Discard"),
+                            Event::RecoveryDiscard(idx)
+                        ),
+                    ]
+                    .spacing(8)
+                    .align_y(Vertical::Center),
+                )
+            },
+        );
+
+        let diff = self.diff.as_ref().map(|(_, changes)| {
+            let lines = changes.iter().fold(
+                Column::new(),
+                |lines, (tag, line)| {
+                    let (sign, color) = match tag {
+                        ChangeTag::Delete => ('-', Color::from_rgb(0.9, 0.4, 0.4)),
+                        _ => ('+', Color::from_rgb(0.4, 0.8, 0.4)),
+                    };
+                    lines.push(
+                        text(format!("{sign} {}", line.trim_end_matches('\n')))
+                            .font(Font::MONOSPACE)
+                            .color(color),
+                    )
+                },
+            );
+            scrollable(lines).height(Length::Fixed(200.0))
+        });
+
+        container(
+            column![text("Unsaved changes were recovered"), entries]
+                .push_maybe(diff)
+                .spacing(settings.ui.spacing),
+        )
+        .style(container::bordered_box)
+        .padding(settings.ui.padding)
+        .width(Length::Fill)
+        .into()
+    }
+
+    fn title(&self) -> String {
+        String::from("Recovery")
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Bottom
+    }
+
+    fn is_open(&self) -> bool {
+        !self.swaps.is_empty()
+    }
+}
@@ -1,73 +1,145 @@
 use crate::editor_core::EditorCore;
-use crate::fileinfo::FileInfo;
-use std::path::PathBuf;
+use crate::fileinfo::{FileInfo, FileKind};
+use crate::filesystem::FileSystem;
+use crate::picker::FilePicker;
+use registers::Event;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-pub async fn scan_file(mut this: EditorCore, path: Option<PathBuf>) -> bool {
-    let path = if let Some(p) = path {
-        p
-    } else {
-        this.files[this.active_file].path.clone()
-    };
-    let mut idx = 0;
-    for (i, f) in this.files.iter().enumerate() {
-        if f.path.to_str().unwrap() == path.to_str().unwrap() {
-            this.files[i].content =
-                std::fs::read_to_string(f.path.clone())
-                    .expect("Unable to read file");
-            idx = i;
-            break;
+/// The schemes of the links opened, others could start programs.
+const LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// The contents of a file as read from disk.
+#[derive(Debug, Clone)]
+pub enum Loaded {
+    Text(String),
+    Binary(Arc<Vec<u8>>),
+}
+
+impl Loaded {
+    pub fn into_info(self, path: PathBuf) -> FileInfo {
+        match self {
+            Loaded::Text(content) => FileInfo::new(path, content),
+            Loaded::Binary(bytes) => FileInfo::binary(path, bytes),
         }
     }
-    idx == this.active_file
 }
 
-pub async fn open_file(mut this: EditorCore) -> Option<(PathBuf, String)> {
-    let file = rfd::FileDialog::new().pick_file();
-    if let Some(file) = file {
-        load_file(&mut this, Some(file)).await;
-        this.active_file = this.files.len() - 1;
-        let info = this.files[this.active_file].clone();
-        Some((info.path, info.content))
-    } else {
-        None
+/// Reads a file, telling text apart from binary data (NUL bytes or 
+/// invalid UTF-8). SVG files are always opened in the viewer.
+pub fn read_path(fs: &dyn FileSystem, path: &Path) -> std::io::Result<Loaded> {
+    let bytes = fs.read(path)?;
+    let is_svg = path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg || bytes.contains(&0) {
+        return Ok(Loaded::Binary(Arc::new(bytes)));
     }
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => Loaded::Text(text),
+        Err(e) => Loaded::Binary(Arc::new(e.into_bytes())),
+    })
 }
 
-pub async fn load_file(this: &mut EditorCore, path: Option<PathBuf>) {
-    let file_path = if let Some(p) = path {
-        p
-    } else {
-        rfd::FileDialog::new().pick_file().unwrap()
-    };
+/// Reads a file away from the UI thread, see [`read_path`].
+pub async fn read_file(
+    fs: Arc<dyn FileSystem>,
+    path: PathBuf,
+) -> (PathBuf, std::io::Result<Loaded>) {
+    smol::unblock(move || {
+        let loaded = read_path(fs.as_ref(), &path);
+        (path, loaded)
+    })
+    .await
+}
 
-    let content = std::fs::read_to_string(&file_path).unwrap();
-    this.files.push(FileInfo::new(file_path, content));
+/// What reading a file to open it leads to.
+pub fn opened(path: PathBuf, loaded: std::io::Result<Loaded>) -> Event {
+    match loaded {
+        Ok(Loaded::Text(content)) => Event::Opened(Some((path, content))),
+        Ok(Loaded::Binary(bytes)) => Event::OpenedBinary(path, bytes),
+        Err(e) => {
+            let error = format!("Unable to open {}: {e}", path.display());
+            Event::FileError(path, error)
+        }
+    }
 }
 
-pub async fn save_file(this: &mut EditorCore, path: Option<PathBuf>) {
-    let file_path = if let Some(p) = path {
-        p
-    } else {
-        rfd::FileDialog::new().save_file().unwrap()
-    };
+/// Reads the text file at `path` again, away from the UI thread.
+pub async fn scan_file(fs: Arc<dyn FileSystem>, path: PathBuf) -> Event {
+    smol::unblock(move || match fs.read_to_string(&path) {
+        Ok(content) => Event::Reloaded(path, content),
+        Err(e) => {
+            let error = format!("Unable to reload {}: {e}", path.display());
+            Event::FileError(path, error)
+        }
+    })
+    .await
+}
 
-    let mut idx = this.files.len() + 1;
-    for (i, f) in this.files.iter().enumerate() {
-        if f.path.to_str().unwrap() == file_path.to_str().unwrap() {
-            idx = i;
-            break;
+pub async fn open_file(this: EditorCore) -> Event {
+    match this.picker.pick_file().await {
+        Some(file) => {
+            let (file, loaded) = read_file(this.fs.clone(), file).await;
+            opened(file, loaded)
         }
+        None => Event::Opened(None),
     }
+}
 
-    if idx < this.files.len() {
-        let content = this.files[idx].content.clone();
-        std::fs::write(file_path.clone(), content)
-            .expect("Unable to write file changes");
-    } else {
-        println!("File not found {:?} on {:?}", file_path, this.files);
+/// Writes the open buffer of `path` away from the UI thread.
+pub async fn save_file(this: &mut EditorCore, path: PathBuf) -> std::io::Result<()> {
+    let Some(info) = this.files.iter().find(|f| f.path == path).cloned() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{path:?} is not open"),
+        ));
+    };
+    let fs = this.fs.clone();
+    smol::unblock(move || write_info(fs.as_ref(), &info, &path)).await
+}
+
+fn write_info(fs: &dyn FileSystem, info: &FileInfo, path: &Path) -> std::io::Result<()> {
+    match &info.kind {
+        FileKind::Text if info.crlf => {
+            let content = info.content.replace("\r\n", "\n");
+            fs.write(path, content.replace('\n', "\r\n").as_bytes())
+        }
+        FileKind::Text => fs.write(path, info.content.as_bytes()),
+        FileKind::Binary(bytes) => fs.write(path, bytes.as_slice()),
+        _ => {
+            println!("Not saving {:?}, it can't be edited", path);
+            Ok(())
+        }
     }
 }
 
+fn is_read_only(fs: &dyn FileSystem, path: &Path) -> bool {
+    fs.metadata(path).is_ok_and(|m| m.read_only)
+}
+
+/// Writes buffers back to their files away from the UI thread, skipping
+/// untitled and read-only ones. Returns the paths that were written with
+/// the digest of what was written, see [`FileInfo::digest`].
+pub async fn save_files(
+    fs: Arc<dyn FileSystem>, 
+    files: Vec<FileInfo>,
+) -> Vec<(PathBuf, u64)> {
+    smol::unblock(move || {
+        files
+            .into_iter()
+            .filter(|f| !f.untitled && !is_read_only(fs.as_ref(), &f.path))
+            .filter_map(|f| match write_info(fs.as_ref(), &f, &f.path) {
+                Ok(()) => Some((f.path.clone(), f.digest())),
+                Err(e) => {
+                    println!("Unable to auto-save {:?}: {e}", f.path);
+                    None
+                }
+            })
+            .collect()
+    })
+    .await
+}
+
 pub async fn quit_file(mut this: EditorCore, idx: usize) -> Option<PathBuf> {
     if this.files.len() >= 1 {
         let ret = this.files.remove(idx);
@@ -86,9 +158,51 @@ pub async fn quit_file(mut this: EditorCore, idx: usize) -> Option<PathBuf> {
     }
 }
 
-pub async fn save_content(mut this: EditorCore) {
-    if this.files.len() >= 1 {
-        let buf = this.files[this.active_file].clone();
-        save_file(&mut this, Some(buf.path)).await;
+/// Writes the active buffer, asking where to put an untitled one. Leads 
+/// to a `Saved` with the path the buffer had and the one it was written 
+/// to, or to a `FileError` when it could not be written.
+pub async fn save_content(mut this: EditorCore) -> Event {
+    let Some(buf) = this.files.get(this.active_file).cloned() else {
+        return Event::Saved(None);
+    };
+    let target = if buf.untitled {
+        let name = buf.path.to_string_lossy().to_string();
+        match this.picker.save_file(&name).await {
+            Some(target) => target,
+            None => return Event::Saved(None),
+        }
+    } else {
+        buf.path.clone()
+    };
+    this.files[this.active_file].path = target.clone();
+    match save_file(&mut this, target.clone()).await {
+        Ok(()) => {
+            let digest = buf.digest();
+            Event::Saved(Some((buf.path, target, digest)))
+        }
+        Err(e) => {
+            let error = format!("Unable to save {}: {e}", target.display());
+            Event::FileError(buf.path, error)
+        }
+    }
+}
+
+pub async fn pick_folder(picker: Arc<dyn FilePicker>) -> Option<PathBuf> {
+    picker.pick_folder().await
+}
+
+/// Opens a web or mail link with the application of the system, without
+/// going through a shell.
+pub async fn open_link(url: String) {
+    let scheme = url.split_once(':')
+        .map(|(scheme, _)| scheme.to_ascii_lowercase());
+    let allowed = scheme.is_some_and(|s| LINK_SCHEMES.contains(&s.as_str()))
+        && !url.chars().any(char::is_control);
+    if !allowed {
+        println!("Not opening {url}, only web and mail links are");
+        return;
+    }
+    if let Err(e) = opener::open(&url) {
+        println!("Unable to open {url}: {e}");
     }
 }
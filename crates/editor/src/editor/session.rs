@@ -0,0 +1,218 @@
+use std::{path::PathBuf, sync::Arc};
+
+use iced::widget::pane_grid::{self, Configuration, Node};
+use registers::SyntaxTheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{filesystem::FileSystem, pane::Pane, zoom::Zoom};
+
+/// The tabs and state of a pane, as saved in the session file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionPane {
+    pub files: Vec<PathBuf>,
+    pub active_file: usize,
+    pub cursor: (usize, usize),
+    pub is_pinned: bool,
+    /// Index, in layout order, of the pane this one previews.
+    pub preview_of: Option<usize>,
+    /// The pane's own zoom, without the global one.
+    #[serde(default)]
+    pub zoom: Zoom,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SessionNode {
+    Split {
+        vertical: bool,
+        ratio: f32,
+        a: Box<SessionNode>,
+        b: Box<SessionNode>,
+    },
+    Pane(SessionPane),
+}
+
+/// What is restored when Zen starts again: the split tree, the tabs of
+/// every pane and the highlighter theme.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub theme: String,
+    pub layout: SessionNode,
+    /// Index, in layout order, of the focused pane.
+    pub focus: Option<usize>,
+}
+
+impl Session {
+    pub fn path() -> Option<PathBuf> {
+        Some(registers::paths::data_dir()?.join("session.json"))
+    }
+
+    pub fn load(fs: &dyn FileSystem) -> Option<Self> {
+        let content = fs.read_to_string(&Self::path()?).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                println!("Ignoring invalid session file: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn store(&self, fs: &dyn FileSystem) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), |dir| fs.create_dir_all(dir))
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(self)
+                    .map_err(std::io::Error::other)?;
+                fs.write(&path, json.as_bytes())
+            });
+        if let Err(e) = written {
+            println!("Unable to write session {:?}: {e}", path);
+        }
+    }
+
+    /// Stores the session away from the UI thread.
+    pub async fn save(self, fs: Arc<dyn FileSystem>) {
+        smol::unblock(move || self.store(fs.as_ref())).await
+    }
+
+    pub fn capture(
+        panes: &pane_grid::State<Pane>,
+        focus: Option<pane_grid::Pane>,
+        theme: SyntaxTheme,
+    ) -> Self {
+        let order = layout_order(panes.layout());
+        let index = |pane: pane_grid::Pane| order.iter().position(|p| *p == pane);
+
+        Self {
+            theme: theme.to_string(),
+            layout: capture_node(panes, panes.layout(), &index),
+            focus: focus.and_then(index),
+        }
+    }
+
+    pub fn theme(&self) -> Option<SyntaxTheme> {
+        (!self.theme.is_empty()).then(|| SyntaxTheme::named(&self.theme))
+    }
+
+    /// The pane states of the layout, in layout order.
+    pub fn panes(&self) -> Vec<&SessionPane> {
+        let mut panes = Vec::new();
+        let mut nodes = vec![&self.layout];
+        while let Some(node) = nodes.pop() {
+            match node {
+                SessionNode::Split { a, b, .. } => {
+                    nodes.push(b);
+                    nodes.push(a);
+                }
+                SessionNode::Pane(pane) => panes.push(pane),
+            }
+        }
+        panes
+    }
+
+    pub fn configuration(
+        &self,
+        mut pane: impl FnMut(&SessionPane) -> Pane,
+    ) -> Configuration<Pane> {
+        build_configuration(&self.layout, &mut pane)
+    }
+}
+
+/// The panes of a layout, depth first and left/top before right/bottom.
+pub fn layout_order(node: &Node) -> Vec<pane_grid::Pane> {
+    match node {
+        Node::Split { a, b, .. } => {
+            let mut order = layout_order(a);
+            order.extend(layout_order(b));
+            order
+        }
+        Node::Pane(pane) => vec![*pane],
+    }
+}
+
+/// The splits of a layout, in the order [`layout_order`] meets them.
+pub fn split_order(node: &Node) -> Vec<pane_grid::Split> {
+    match node {
+        Node::Split { id, a, b, .. } => {
+            let mut order = vec![*id];
+            order.extend(split_order(a));
+            order.extend(split_order(b));
+            order
+        }
+        Node::Pane(_) => Vec::new(),
+    }
+}
+
+fn capture_node(
+    panes: &pane_grid::State<Pane>,
+    node: &Node,
+    index: &impl Fn(pane_grid::Pane) -> Option<usize>,
+) -> SessionNode {
+    match node {
+        Node::Split {
+            axis, ratio, a, b, ..
+        } => SessionNode::Split {
+            vertical: *axis == pane_grid::Axis::Vertical,
+            ratio: *ratio,
+            a: Box::new(capture_node(panes, a, index)),
+            b: Box::new(capture_node(panes, b, index)),
+        },
+        Node::Pane(id) => {
+            let Some(pane) = panes.get(*id) else {
+                return SessionNode::Pane(SessionPane::default());
+            };
+            // Untitled buffers come back through the recovery directory
+            let files: Vec<PathBuf> = pane
+                .core
+                .files
+                .iter()
+                .filter(|f| !f.untitled)
+                .map(|f| f.path.clone())
+                .collect();
+            let active_file = pane
+                .core
+                .active_path()
+                .and_then(|active| files.iter().position(|f| f == active))
+                .unwrap_or(0);
+            SessionNode::Pane(SessionPane {
+                files,
+                active_file,
+                cursor: pane.core.cursor_position(),
+                is_pinned: pane.is_pinned,
+                preview_of: pane
+                    .preview
+                    .as_ref()
+                    .and_then(|preview| index(preview.source)),
+                zoom: pane.core.pane_zoom(),
+            })
+        }
+    }
+}
+
+fn build_configuration(
+    node: &SessionNode,
+    pane: &mut impl FnMut(&SessionPane) -> Pane,
+) -> Configuration<Pane> {
+    match node {
+        SessionNode::Split {
+            vertical,
+            ratio,
+            a,
+            b,
+        } => Configuration::Split {
+            axis: if *vertical {
+                pane_grid::Axis::Vertical
+            } else {
+                pane_grid::Axis::Horizontal
+            },
+            ratio: ratio.clamp(0.05, 0.95),
+            a: Box::new(build_configuration(a, pane)),
+            b: Box::new(build_configuration(b, pane)),
+        },
+        SessionNode::Pane(state) => Configuration::Pane(pane(state)),
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use registers::{Event, MarkerKind, StatusItem};
+
+use crate::{
+    editor_core::EditorCore, fileinfo::FileKind, filesystem::FileSystem, highlighter,
+};
+
+/// The status bar items describing the active buffer of `core`.
+pub fn items(core: &EditorCore, branch: Option<&str>) -> Vec<StatusItem> {
+    let mut items = Vec::new();
+    if let Some(branch) = branch {
+        items.push(StatusItem::left(branch).on_click(Event::RefreshBranch));
+    }
+    if let Some(error) = core.file_error() {
+        items.push(
+            StatusItem::left(format!("⚠ {error}")).on_click(Event::ClearFileError),
+        );
+    }
+    let Some(file) = core.files.get(core.active_file) else {
+        items.push(zoom(core));
+        return items;
+    };
+
+    let errors = file.markers.iter()
+        .filter(|m| m.kind == MarkerKind::Error)
+        .count();
+    let warnings = file.markers.iter()
+        .filter(|m| m.kind == MarkerKind::Warning)
+        .count();
+    let first = file.markers.iter()
+        .filter(|m| matches!(m.kind, MarkerKind::Error | MarkerKind::Warning))
+        .map(|m| m.line)
+        .min();
+    // Shown once a register marks the file, clicking reveals the first
+    if let Some(line) = first {
+        items.push(
+            StatusItem::left(format!("{errors} ✗ {warnings} ⚠"))
+                .on_click(Event::RevealLine(line)),
+        );
+    }
+    if let Some(saved) = core.auto_saved() {
+        items.push(
+            StatusItem::left(format!("Auto-saved {}", saved.format("%r")))
+                .on_click(Event::Save),
+        );
+    }
+    items.push(
+        StatusItem::left(format!("Modified {}", file.last_mod.format("%v %r")))
+            .on_click(Event::ScanFile(None)),
+    );
+
+    if file.kind.is_text() {
+        let (line, column) = core.cursor_position();
+        items.push(StatusItem::right(format!(
+            "Ln {}, Col {} ({} lines)",
+            line + 1,
+            column + 1,
+            core.line_count()
+        )));
+        let selected = core.selection_len();
+        if selected > 0 {
+            items.push(StatusItem::right(format!("{selected} selected")));
+        }
+        items.push(StatusItem::right(indentation(&file.content)));
+        items.push(StatusItem::right("UTF-8"));
+        items.push(
+            StatusItem::right(if file.crlf { "CRLF" } else { "LF" })
+                .on_click(Event::ToggleLineEndings),
+        );
+    } else if matches!(file.kind, FileKind::Binary(_)) {
+        items.push(StatusItem::right("Binary"));
+    }
+    items.push(
+        StatusItem::right(language(&file.path)).on_click(Event::ToggleSettings),
+    );
+    items.push(zoom(core));
+    items
+}
+
+fn zoom(core: &EditorCore) -> StatusItem {
+    StatusItem::right(format!("{}%", core.zoom().percent()))
+        .on_click(Event::ZoomReset)
+}
+
+fn language(path: &Path) -> String {
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match highlighter::language_name(&extension) {
+        Some(name) if !extension.is_empty() => name.to_string(),
+        _ => String::from("Plain Text"),
+    }
+}
+
+/// Guesses the indentation of a text from the lines that are indented.
+fn indentation(text: &str) -> String {
+    let (mut tabs, mut spaces, mut width) = (0, 0, usize::MAX);
+    for line in text.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if indent.starts_with('\t') {
+            tabs += 1;
+        } else if !indent.is_empty() {
+            spaces += 1;
+            width = width.min(indent.len());
+        }
+    }
+    if tabs > spaces {
+        String::from("Tabs")
+    } else if spaces > 0 {
+        format!("Spaces: {width}")
+    } else {
+        String::from("Spaces: 4")
+    }
+}
+
+/// The checked out branch of the repository in `fs` holding `path`, or the
+/// short commit hash when the head is detached.
+pub fn git_branch(fs: &dyn FileSystem, path: &Path) -> Option<String> {
+    let head = path.ancestors()
+        .map(|dir| dir.join(".git").join("HEAD"))
+        .find(|head| fs.is_file(head))?;
+    let head = fs.read_to_string(&head).ok()?;
+    let head = head.trim();
+    Some(match head.strip_prefix("ref: ") {
+        Some(reference) => reference
+            .strip_prefix("refs/heads/")
+            .unwrap_or(reference)
+            .to_string(),
+        None => head.chars().take(7).collect(),
+    })
+}
+
+/// The directory whose repository decides the branch shown for `path`.
+pub fn branch_key(path: &Path) -> PathBuf {
+    path.parent().map_or_else(PathBuf::new, Path::to_path_buf)
+}
@@ -0,0 +1,139 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::fileinfo::{fnv1a, FileInfo};
+use crate::filesystem::FileSystem;
+
+/// A copy of an unsaved buffer, kept in the recovery directory until the
+/// buffer is saved or closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Swap {
+    pub path: PathBuf,
+    pub untitled: bool,
+    pub content: String,
+    /// Unix timestamp of when the swap was written.
+    pub written: i64,
+    /// The swap file it was read from, by [`pending`].
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+}
+
+impl Swap {
+    pub fn of(info: &FileInfo) -> Self {
+        Self {
+            path: info.path.clone(),
+            untitled: info.untitled,
+            content: info.content.clone(),
+            written: Local::now().timestamp(),
+            file: None,
+        }
+    }
+}
+
+pub fn recovery_dir() -> Option<PathBuf> {
+    Some(registers::paths::data_dir()?.join("recovery"))
+}
+
+/// The swap file of the buffer at `path`, named after a hash of the path
+/// that stays the same from one build to the next.
+fn swap_path(path: &Path) -> Option<PathBuf> {
+    let hash = fnv1a(path.to_string_lossy().as_bytes());
+    Some(recovery_dir()?.join(format!("{hash:016x}.swap")))
+}
+
+
+/// A name for a new untitled buffer that no pending swap file in `fs`
+/// uses.
+pub fn untitled_path(fs: &dyn FileSystem) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(1);
+    loop {
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let path = PathBuf::from(format!("Untitled-{n}"));
+        if !swap_path(&path).is_some_and(|f| fs.is_file(&f)) {
+            return path;
+        }
+    }
+}
+
+pub fn write_swap(fs: &dyn FileSystem, swap: &Swap) {
+    let Some(file) = swap_path(&swap.path) else {
+        return;
+    };
+    let written = fs.create_dir_all(file.parent().unwrap_or(&file))
+        .and_then(|_| {
+            let json =
+                serde_json::to_string(swap).map_err(std::io::Error::other)?;
+            fs.write(&file, json.as_bytes())
+        });
+    if let Err(e) = written {
+        println!("Unable to write swap file {:?}: {e}", file);
+    }
+}
+
+/// Writes the swaps away from the UI thread.
+pub async fn write_swaps(fs: Arc<dyn FileSystem>, swaps: Vec<Swap>) {
+    smol::unblock(move || {
+        swaps.iter().for_each(|swap| write_swap(fs.as_ref(), swap));
+    })
+    .await
+}
+
+pub fn remove_swap(fs: &dyn FileSystem, path: &Path) {
+    if let Some(file) = swap_path(path).filter(|f| fs.is_file(f)) {
+        if let Err(e) = fs.remove_file(&file) {
+            println!("Unable to remove swap file {:?}: {e}", file);
+        }
+    }
+}
+
+/// Removes the swap file `swap` was read from, unless it was written again
+/// since, as its buffer was edited in this run.
+pub fn discard(fs: &dyn FileSystem, swap: &Swap) {
+    let Some(file) = swap.file.clone().or_else(|| swap_path(&swap.path)) else {
+        return;
+    };
+    let current: Option<Swap> = fs.read_to_string(&file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let unchanged = current.is_some_and(|current| {
+        current.written == swap.written && current.content == swap.content
+    });
+    if !unchanged {
+        return;
+    }
+    if let Err(e) = fs.remove_file(&file) {
+        println!("Unable to remove swap file {:?}: {e}", file);
+    }
+}
+
+/// The swap files left behind in `fs` by a previous run.
+pub fn pending(fs: &dyn FileSystem) -> Vec<Swap> {
+    let Some(entries) = recovery_dir().and_then(|d| fs.list_dir(&d).ok())
+    else {
+        return Vec::new();
+    };
+    let mut swaps: Vec<Swap> = entries
+        .into_iter()
+        .filter_map(|path| {
+            if path.extension()? != "swap" {
+                return None;
+            }
+            let json = fs.read_to_string(&path).ok()?;
+            let swap: Swap = serde_json::from_str(&json).ok()?;
+            Some(Swap {
+                file: Some(path),
+                ..swap
+            })
+        })
+        .collect();
+    swaps.sort_by_key(|s| s.written);
+    swaps
+}
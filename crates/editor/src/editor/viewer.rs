@@ -0,0 +1,213 @@
+use iced::{
+    alignment::Vertical,
+    mouse,
+    widget::{
+        canvas::{self, Frame, Geometry},
+        column, container, horizontal_space, image, row, scrollable, stack,
+        svg, text,
+    },
+    Color, ContentFit, Element, Length, Point, Rectangle, Renderer, Size,
+    Theme,
+};
+use registers::Event;
+use ui::labeled_button;
+
+use crate::fileinfo::FileKind;
+
+const CHECKER_SIZE: f32 = 8.0;
+const ZOOM_STEP: f32 = 1.25;
+
+/// How an image or SVG tab is currently displayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewerState {
+    pub zoom: f32,
+    pub fit: bool,
+}
+
+impl Default for ViewerState {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            fit: true,
+        }
+    }
+}
+
+impl ViewerState {
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * ZOOM_STEP).min(32.0);
+        self.fit = false;
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / ZOOM_STEP).max(1.0 / 32.0);
+        self.fit = false;
+    }
+
+    pub fn actual_size(&mut self) {
+        self.zoom = 1.0;
+        self.fit = false;
+    }
+
+    pub fn fit(&mut self) {
+        self.fit = true;
+    }
+}
+
+/// A transparency checkerboard drawn behind an image of `size`, where
+/// the image is once made to fit the bounds.
+struct Checkerboard {
+    size: Option<(u32, u32)>,
+}
+
+impl Checkerboard {
+    /// The rectangle an image fitted with `ContentFit::Contain` covers,
+    /// the whole bounds when its size is unknown.
+    fn area(&self, bounds: Size) -> Rectangle {
+        let Some((width, height)) = self.size.filter(|(w, h)| *w > 0 && *h > 0)
+        else {
+            return Rectangle::with_size(bounds);
+        };
+        let scale = (bounds.width / width as f32)
+            .min(bounds.height / height as f32);
+        let fitted = Size::new(width as f32 * scale, height as f32 * scale);
+        Rectangle::new(
+            Point::new(
+                (bounds.width - fitted.width) / 2.0,
+                (bounds.height - fitted.height) / 2.0,
+            ),
+            fitted,
+        )
+    }
+}
+
+impl canvas::Program<Event> for Checkerboard {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let palette = theme.extended_palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+        let (light, dark) = (
+            palette.background.weak.color,
+            Color {
+                a: 0.5,
+                ..palette.background.strong.color
+            },
+        );
+        let area = self.area(bounds.size());
+        frame.fill_rectangle(area.position(), area.size(), light);
+
+        let columns = (area.width / CHECKER_SIZE).ceil() as usize;
+        let rows = (area.height / CHECKER_SIZE).ceil() as usize;
+        for y in 0..rows {
+            for x in (y % 2..columns).step_by(2) {
+                let left = x as f32 * CHECKER_SIZE;
+                let top = y as f32 * CHECKER_SIZE;
+                // The last squares are cut by the edges of the image
+                frame.fill_rectangle(
+                    Point::new(area.x + left, area.y + top),
+                    Size::new(
+                        CHECKER_SIZE.min(area.width - left),
+                        CHECKER_SIZE.min(area.height - top),
+                    ),
+                    dark,
+                );
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+pub fn view(kind: FileKind, state: ViewerState) -> Element<'static, Event> {
+    let (picture, size, format): (Element<'static, Event>, _, _) = match kind
+    {
+        FileKind::Image { handle, size } => {
+            let (width, height) = sized(size, state);
+            (
+                image(handle)
+                    .width(width)
+                    .height(height)
+                    .content_fit(ContentFit::Contain)
+                    .into(),
+                size,
+                "Image",
+            )
+        }
+        FileKind::Svg { handle, size } => {
+            let (width, height) = sized(size, state);
+            (
+                svg(handle)
+                    .width(width)
+                    .height(height)
+                    .content_fit(ContentFit::Contain)
+                    .into(),
+                size,
+                "SVG",
+            )
+        }
+        _ => return text("This file can not be displayed").into(),
+    };
+
+    let (width, height) = sized(size, state);
+    let board = canvas::Canvas::new(Checkerboard { size })
+        .width(width)
+        .height(height);
+    let picture: Element<'static, Event> = if state.fit {
+        container(stack![board, picture]).center(Length::Fill).into()
+    } else {
+        scrollable(container(stack![board, picture]).padding(4))
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Scrollbar::default(),
+                horizontal: scrollable::Scrollbar::default(),
+            })
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    };
+
+    let dimensions = match size {
+        Some((w, h)) => format!("{format} | {w} × {h} px"),
+        None => format!("{format} | unknown size"),
+    };
+    let zoom = if state.fit {
+        "Fit".to_string()
+    } else {
+        format!("{:.0}%", state.zoom * 100.0)
+    };
+
+    let controls = row![
+        text(dimensions),
+        horizontal_space(),
+        text(zoom),
+        labeled_button("-", Event::ImageZoomOut),
+        labeled_button("+", Event::ImageZoomIn),
+        labeled_button("1:1", Event::ImageActualSize),
+        labeled_button("Fit", Event::ImageFit),
+    ]
+    .spacing(5)
+    .align_y(Vertical::Center)
+    .padding(2);
+
+    column![controls, picture]
+        .spacing(4)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+fn sized(size: Option<(u32, u32)>, state: ViewerState) -> (Length, Length) {
+    match size {
+        Some((w, h)) if !state.fit => (
+            Length::Fixed(w as f32 * state.zoom),
+            Length::Fixed(h as f32 * state.zoom),
+        ),
+        _ => (Length::Fill, Length::Fill),
+    }
+}
@@ -0,0 +1,103 @@
+use iced::{
+    alignment::{Horizontal, Vertical},
+    widget::{button, column, container, row, scrollable, text, Column},
+    Element, Length,
+};
+use registers::{commands, Event};
+use settings::recent::{self, RecentEntry};
+use ui::labeled_button;
+
+/// Recent files and folders listed on the start page.
+const RECENT_ENTRIES: usize = 8;
+
+/// The commands whose key bindings the start page teaches.
+fn common_commands() -> [(&'static str, Event); 8] {
+    [
+        ("New file", Event::NewFile),
+        ("Open file", Event::OpenFile),
+        ("Open folder", Event::OpenFolder),
+        ("Quick open", Event::ToggleQuickOpen),
+        ("Save", Event::Save),
+        ("Close file", Event::Quit(None)),
+        ("Settings", Event::ToggleSettings),
+        ("Zoom in", Event::ZoomIn),
+    ]
+}
+
+/// The start page of a pane without files.
+pub fn view() -> Element<'static, Event> {
+    let start = column![
+        text("Start").size(20),
+        row![
+            labeled_button("New File", Event::NewFile),
+            labeled_button("Open File...", Event::OpenFile),
+            labeled_button("Open Folder...", Event::OpenFolder),
+        ]
+        .spacing(8),
+    ]
+    .spacing(8);
+
+    let recent = recent::current();
+    let entries = recent.files.iter()
+        .take(RECENT_ENTRIES)
+        .map(|e| (e, Event::OpenPath(e.path.clone())))
+        .chain(recent.folders.iter()
+            .take(RECENT_ENTRIES)
+            .map(|e| (e, Event::FolderOpened(e.path.clone()))))
+        .fold(Column::new().spacing(2), |entries, (entry, open)| {
+            entries.push(recent_entry(entry, open))
+        });
+    let recent = if recent.files.is_empty() && recent.folders.is_empty() {
+        column![text("Recent").size(20), text("Nothing opened yet")]
+    } else {
+        column![text("Recent").size(20), entries]
+    };
+
+    let keys = common_commands().into_iter().fold(
+        Column::new().spacing(4),
+        |keys, (label, event)| {
+            let shortcut = commands::shortcut(&event)
+                .unwrap_or_else(|| String::from("-"));
+            keys.push(row![
+                text(label).width(Length::Fixed(140.0)),
+                text(shortcut).color(iced::color!(0xc2c2c2)),
+            ])
+        },
+    );
+
+    let page = row![
+        column![start, recent].spacing(24).width(Length::FillPortion(3)),
+        column![text("Key bindings").size(20), keys]
+            .spacing(8)
+            .width(Length::FillPortion(2)),
+    ]
+    .spacing(32)
+    .padding(24);
+
+    scrollable(
+        column![text("Zen Editor").size(32), page]
+            .align_x(Horizontal::Center)
+            .width(Length::Fill),
+    )
+    .height(Length::Fill)
+    .into()
+}
+
+fn recent_entry(entry: &RecentEntry, open: Event) -> Element<'static, Event> {
+    let dir = entry.path.parent()
+        .map_or(String::new(), |d| d.display().to_string());
+    row![
+        button(text(entry.name()))
+            .style(button::text)
+            .on_press(open),
+        container(text(dir).size(12).color(iced::color!(0xc2c2c2)))
+            .width(Length::Fill),
+        text(entry.age()).size(12),
+        button(text("×"))
+            .style(button::text)
+            .on_press(Event::RemoveRecent(entry.path.clone())),
+    ]
+    .spacing(8)
+    .align_y(Vertical::Center)
+    .into()
+}
@@ -0,0 +1,54 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// Added or removed by every zoom step.
+const STEP: f32 = 0.1;
+const MIN: f32 = 0.5;
+const MAX: f32 = 3.0;
+
+/// The zoom applied on top of every pane's own zoom.
+static GLOBAL: Mutex<Zoom> = Mutex::new(Zoom::ACTUAL);
+
+/// A scale for the font size and padding of an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Zoom(f32);
+
+impl Zoom {
+    pub const ACTUAL: Self = Self(1.0);
+
+    pub fn zoom_in(self) -> Self {
+        Self((self.0 + STEP).min(MAX))
+    }
+
+    pub fn zoom_out(self) -> Self {
+        Self((self.0 - STEP).max(MIN))
+    }
+
+    pub fn factor(self) -> f32 {
+        self.0
+    }
+
+    /// Combines a pane's zoom with the global one.
+    pub fn with(self, other: Self) -> Self {
+        Self((self.0 * other.0).clamp(MIN * MIN, MAX * MAX))
+    }
+
+    pub fn percent(self) -> u32 {
+        (self.0 * 100.0).round() as u32
+    }
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self::ACTUAL
+    }
+}
+
+pub fn global() -> Zoom {
+    *GLOBAL.lock().expect("Let's hope the lock isn't poisoned")
+}
+
+pub fn set_global(zoom: Zoom) {
+    *GLOBAL.lock().expect("Let's hope the lock isn't poisoned") = zoom;
+}
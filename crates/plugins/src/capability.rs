@@ -0,0 +1,70 @@
+use std::{fmt, str::FromStr};
+
+use crate::ApiVersion;
+
+/// Something a plugin may do through its [`Context`](crate::Context).
+/// Lifecycle events are sent to every plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Ask for the text of open buffers.
+    ReadBuffers,
+    /// Insert text into open buffers or replace it.
+    EditBuffers,
+    /// Add entries to the menus.
+    Commands,
+    KeyBindings,
+    StatusItems,
+    /// Show a panel of its own.
+    Panels,
+    /// Read and write the clipboard history and the named registers.
+    Clipboard,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Parses the names manifest files use, like `read-buffers`.
+impl FromStr for Capability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read-buffers" => Ok(Self::ReadBuffers),
+            "edit-buffers" => Ok(Self::EditBuffers),
+            "commands" => Ok(Self::Commands),
+            "key-bindings" => Ok(Self::KeyBindings),
+            "status-items" => Ok(Self::StatusItems),
+            "panels" => Ok(Self::Panels),
+            "clipboard" => Ok(Self::Clipboard),
+            _ => Err(format!("Unknown permission {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    /// The plugin was built against an API this editor doesn't provide.
+    Incompatible { plugin: ApiVersion, host: ApiVersion },
+    /// The plugin wasn't granted what it tried to do.
+    Denied(Capability),
+    /// A loaded register or a built-in one already goes by this name.
+    NameTaken(String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incompatible { plugin, host } => write!(
+                f,
+                "built against plugin API {plugin}, the editor provides {host}"
+            ),
+            Self::Denied(capability) => write!(f, "{capability} not granted"),
+            Self::NameTaken(name) => write!(f, "the name {name} is taken"),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
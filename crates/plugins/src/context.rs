@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use iced::keyboard::Modifiers;
+use registers::{clipboard, commands, Command, Event, MenuKind, StatusItem, Target};
+
+use crate::{Capability, PluginError};
+
+/// What a plugin is handed to act on the editor. Every call checks the
+/// capability it needs, and what it sends is run once the plugin returns.
+#[derive(Debug, Clone)]
+pub struct Context {
+    name: String,
+    granted: Vec<Capability>,
+    pending: Vec<Event>,
+}
+
+impl Context {
+    pub fn new(name: impl Into<String>, granted: Vec<Capability>) -> Self {
+        Self {
+            name: name.into(),
+            granted,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The name of the plugin, which is also the name of its register.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+
+    fn require(&self, capability: Capability) -> Result<(), PluginError> {
+        if self.has(capability) {
+            Ok(())
+        } else {
+            Err(PluginError::Denied(capability))
+        }
+    }
+
+    /// The event running the plugin's command `id`, for status items and
+    /// widgets of its panel.
+    pub fn command(&self, id: impl Into<String>) -> Event {
+        Target::register(self.name.clone()).send(Event::PluginCommand(id.into()))
+    }
+
+    /// Asks for the text of the open buffer at `path`, which comes back as
+    /// [`Lifecycle::BufferRead`](crate::Lifecycle::BufferRead).
+    pub fn read_buffer(&mut self, path: impl Into<PathBuf>) -> Result<(), PluginError> {
+        self.require(Capability::ReadBuffers)?;
+        self.pending.push(Event::ReadBuffer(path.into(), self.name.clone()));
+        Ok(())
+    }
+
+    /// Pastes `text` at the cursor of the buffer at `path`.
+    pub fn insert_text(
+        &mut self,
+        path: impl Into<PathBuf>,
+        text: impl Into<String>,
+    ) -> Result<(), PluginError> {
+        self.require(Capability::EditBuffers)?;
+        self.pending.push(Target::file(path).send(Event::InsertText(text.into())));
+        Ok(())
+    }
+
+    /// Replaces the whole text of the buffer at `path`, as one undo step.
+    pub fn replace_buffer(
+        &mut self,
+        path: impl Into<PathBuf>,
+        text: impl Into<String>,
+    ) -> Result<(), PluginError> {
+        self.require(Capability::EditBuffers)?;
+        self.pending.push(Target::file(path).send(Event::ReplaceBuffer(text.into())));
+        Ok(())
+    }
+
+    /// Lists the command `id` in a menu, see [`Lifecycle::Command`](crate::Lifecycle::Command).
+    pub fn register_command(
+        &mut self,
+        menu: MenuKind,
+        label: impl Into<String>,
+        id: impl Into<String>,
+    ) -> Result<(), PluginError> {
+        self.require(Capability::Commands)?;
+        commands::register(Command::new(menu, label, self.command(id)));
+        Ok(())
+    }
+
+    /// Runs the command `id` when `key` is released with `modifiers`.
+    pub fn bind_key(
+        &mut self,
+        key: char,
+        modifiers: Modifiers,
+        id: impl Into<String>,
+    ) -> Result<(), PluginError> {
+        self.require(Capability::KeyBindings)?;
+        let command = Box::new(self.command(id));
+        self.pending.push(Event::BindKey(key, modifiers, command));
+        Ok(())
+    }
+
+    /// Replaces the plugin's status bar items. Clicks may only run the
+    /// plugin's own commands, other ones are dropped.
+    pub fn set_status(&mut self, items: Vec<StatusItem>) -> Result<(), PluginError> {
+        self.require(Capability::StatusItems)?;
+        let items = items
+            .into_iter()
+            .map(|mut item| {
+                item.command = item.command.filter(|event| self.owns(event));
+                item
+            })
+            .collect();
+        self.pending.push(Event::SetStatusItems(self.name.clone(), items));
+        Ok(())
+    }
+
+    /// The text copied lately, the newest first.
+    pub fn clipboard_history(&self) -> Result<Vec<String>, PluginError> {
+        self.require(Capability::Clipboard)?;
+        Ok(clipboard::current().history)
+    }
+
+    /// Adds `text` to the clipboard history, as if it was copied.
+    pub fn push_clipboard(&mut self, text: impl Into<String>) -> Result<(), PluginError> {
+        self.require(Capability::Clipboard)?;
+        clipboard::push(text.into());
+        Ok(())
+    }
+
+    /// The text of the register `name`, from `a` to `z`.
+    pub fn read_register(&self, name: char) -> Result<Option<String>, PluginError> {
+        self.require(Capability::Clipboard)?;
+        Ok(clipboard::register(name))
+    }
+
+    /// Writes `text` to the register `name`, or appends it when `name` is
+    /// uppercase. Returns whether `name` is a register.
+    pub fn write_register(
+        &mut self,
+        name: char,
+        text: impl Into<String>,
+    ) -> Result<bool, PluginError> {
+        self.require(Capability::Clipboard)?;
+        Ok(clipboard::set_register(name, text.into()))
+    }
+
+    /// Whether `event` runs one of the plugin's commands.
+    fn owns(&self, event: &Event) -> bool {
+        matches!(
+            event,
+            Event::To(target, event)
+                if target.register.as_deref() == Some(self.name.as_str())
+                    && matches!(**event, Event::PluginCommand(_))
+        )
+    }
+
+    /// What the plugin sent since the last call.
+    pub fn take(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.pending)
+    }
+}
@@ -0,0 +1,104 @@
+use iced::{widget::Column, Element, Task};
+use registers::{Dock, Event, Register, Target};
+
+use crate::{Capability, Context, Lifecycle, Plugin, PluginError, API_VERSION};
+
+/// Names of the built-in registers, which plugins can't take even when the
+/// register isn't loaded.
+pub const RESERVED_NAMES: [&str; 7] = [
+    "editor",
+    "Register",
+    "Quick Open",
+    "File Browser",
+    "Clipboard",
+    "Recovery",
+    "Settings",
+];
+
+/// The register a plugin runs in. It turns editor events into
+/// [`Lifecycle`] events and runs what the plugin sent through its context.
+pub struct PluginHost {
+    plugin: Box<dyn Plugin>,
+    context: Context,
+}
+
+impl PluginHost {
+    /// Activates `plugin`, granting the capabilities of its manifest that
+    /// are also `granted`, by the user or the application. The task runs
+    /// what it did on activation.
+    pub fn load(
+        plugin: Box<dyn Plugin>,
+        granted: &[Capability],
+    ) -> Result<(Self, Task<Event>), PluginError> {
+        let manifest = plugin.manifest();
+        if !manifest.api.runs_on(API_VERSION) {
+            return Err(PluginError::Incompatible {
+                plugin: manifest.api,
+                host: API_VERSION,
+            });
+        }
+        if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(&manifest.name)) {
+            return Err(PluginError::NameTaken(manifest.name));
+        }
+        let capabilities = manifest.capabilities.into_iter()
+            .filter(|capability| granted.contains(capability))
+            .collect();
+        let mut host = Self {
+            plugin,
+            context: Context::new(manifest.name, capabilities),
+        };
+        let task = host.notify(vec![Lifecycle::Activated]);
+        Ok((host, task))
+    }
+
+    fn notify(&mut self, events: Vec<Lifecycle>) -> Task<Event> {
+        for event in events {
+            self.plugin.on(&mut self.context, event);
+        }
+        Task::batch(self.context.take().into_iter().map(Task::done))
+    }
+}
+
+impl Register for PluginHost {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        self.notify(Lifecycle::of(&event))
+    }
+
+    fn route(&mut self, _target: Target, event: Event) -> Task<Event> {
+        match event {
+            Event::BufferRead(path, text) => {
+                self.notify(vec![Lifecycle::BufferRead(path, text)])
+            }
+            Event::PluginCommand(id) => self.notify(vec![Lifecycle::Command(id)]),
+            event => self.update(event),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        let panel = self.context.has(Capability::Panels)
+            .then(|| self.plugin.panel())
+            .flatten();
+        match panel {
+            Some(panel) => {
+                let name = self.context.name().to_string();
+                panel.map(move |id| {
+                    Target::register(name.clone()).send(Event::PluginCommand(id))
+                })
+            }
+            None => Column::new().into(),
+        }
+    }
+
+    fn title(&self) -> String {
+        self.context.name().to_string()
+    }
+
+    /// Panels of plugins go in the right sidebar.
+    fn dock(&self) -> Dock {
+        Dock::Right
+    }
+
+    fn is_open(&self) -> bool {
+        self.context.has(Capability::Panels) && self.plugin.panel().is_some()
+    }
+}
@@ -0,0 +1,33 @@
+//! The API plugins of the editor are written against.
+//!
+//! A [`Plugin`] is loaded in a [`PluginHost`], a register of its own, and
+//! only reaches the editor through the [`Context`] it is handed. The context
+//! refuses whatever the plugin didn't both ask for in its [`Manifest`] and
+//! get granted when loaded, see [`Capability`].
+//!
+//! Plugins declare the [`API_VERSION`] they were built against. Adding to
+//! the API bumps the minor version, changing or removing anything bumps the
+//! major one, and the host loads plugins of its major version built against
+//! the same or an older minor one.
+
+pub mod version;
+pub use version::{ApiVersion, API_VERSION};
+
+pub mod capability;
+pub use capability::{Capability, PluginError};
+
+pub mod lifecycle;
+pub use lifecycle::Lifecycle;
+
+pub mod context;
+pub use context::Context;
+
+pub mod plugin;
+pub use plugin::{Manifest, Plugin};
+
+pub mod host;
+pub use host::{PluginHost, RESERVED_NAMES};
+
+// What plugins need without depending on the other crates
+pub use iced::keyboard::Modifiers;
+pub use registers::{MenuKind, StatusItem, StatusSide};
@@ -0,0 +1,40 @@
+use std::{path::PathBuf, sync::Arc};
+
+use registers::Event;
+
+/// What happened in the editor, as told to a plugin.
+#[derive(Debug, Clone)]
+pub enum Lifecycle {
+    /// The plugin was loaded.
+    Activated,
+    FileOpened(PathBuf),
+    FileSaved(PathBuf),
+    FileClosed(PathBuf),
+    /// The focused editor shows another file, or none.
+    FocusChanged(Option<PathBuf>),
+    /// The text of a buffer asked for with
+    /// [`Context::read_buffer`](crate::Context::read_buffer).
+    BufferRead(PathBuf, Arc<String>),
+    /// One of the plugin's commands was run, by its id.
+    Command(String),
+}
+
+impl Lifecycle {
+    /// The lifecycle events behind an editor event, addressed events
+    /// included.
+    pub fn of(event: &Event) -> Vec<Self> {
+        match event {
+            Event::To(_, event) => Self::of(event),
+            Event::Opened(Some((path, _))) | Event::OpenedBinary(path, _) => {
+                vec![Self::FileOpened(path.clone())]
+            }
+            Event::Saved(Some((_, path, _))) => vec![Self::FileSaved(path.clone())],
+            Event::AutoSaved(saved) => saved.iter()
+                .map(|(path, _)| Self::FileSaved(path.clone()))
+                .collect(),
+            Event::Quited(path) => vec![Self::FileClosed(path.clone())],
+            Event::FocusChanged(path) => vec![Self::FocusChanged(path.clone())],
+            _ => Vec::new(),
+        }
+    }
+}
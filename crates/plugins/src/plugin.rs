@@ -0,0 +1,50 @@
+use iced::Element;
+
+use crate::{ApiVersion, Capability, Context, Lifecycle, API_VERSION};
+
+/// Who a plugin is and what it needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Unique among the loaded plugins, it names the plugin's register.
+    pub name: String,
+    pub version: String,
+    /// The plugin API the plugin was built against.
+    pub api: ApiVersion,
+    pub capabilities: Vec<Capability>,
+}
+
+impl Manifest {
+    /// A manifest built against this crate's [`API_VERSION`], asking for
+    /// nothing yet.
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            api: API_VERSION,
+            capabilities: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, capability: Capability) -> Self {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+}
+
+/// An extension of the editor, loaded through a
+/// [`PluginHost`](crate::PluginHost).
+pub trait Plugin {
+    fn manifest(&self) -> Manifest;
+
+    /// Called for [`Lifecycle::Activated`] first, then for everything the
+    /// plugin is told about.
+    fn on(&mut self, _cx: &mut Context, _event: Lifecycle) {}
+
+    /// The plugin's panel, shown with [`Capability::Panels`]. Widgets send
+    /// the ids of the plugin's commands.
+    fn panel(&self) -> Option<Element<'_, String>> {
+        None
+    }
+}
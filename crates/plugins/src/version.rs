@@ -0,0 +1,45 @@
+use std::{fmt, str::FromStr};
+
+/// The version of the plugin API this editor provides. 1.2 added the events
+/// of scripts, macros, the file browser and docks, and made
+/// [`Event`](registers::Event) non-exhaustive.
+pub const API_VERSION: ApiVersion = ApiVersion::new(1, 2);
+
+/// A version of the plugin API, see the [crate](crate) documentation for
+/// when each part changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ApiVersion {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Whether a plugin built against this version runs on `host`.
+    pub fn runs_on(&self, host: ApiVersion) -> bool {
+        self.major == host.major && self.minor <= host.minor
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Parses `major.minor`.
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid API version {s}");
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+        Ok(Self::new(
+            major.parse().map_err(|_| invalid())?,
+            minor.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
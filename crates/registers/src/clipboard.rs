@@ -0,0 +1,152 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard, OnceLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Copies and cuts kept in the history.
+const HISTORY_LEN: usize = 30;
+/// Longest entry written to the clipboard file, in bytes. Longer ones are
+/// only kept in memory.
+const MAX_STORED_LEN: usize = 64 * 1024;
+
+/// Whether the clipboard is written to its file.
+static PERSISTED: AtomicBool = AtomicBool::new(true);
+
+/// The text copied lately and the named registers, kept across sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Clipboard {
+    /// The newest copy first.
+    pub history: Vec<String>,
+    /// Registers `a` to `z`, by their lowercase name.
+    pub named: BTreeMap<String, String>,
+}
+
+impl Clipboard {
+    pub fn path() -> Option<PathBuf> {
+        Some(crate::paths::data_dir()?.join("clipboard.json"))
+    }
+
+    fn load() -> Self {
+        if !PERSISTED.load(Ordering::Relaxed) {
+            return Self::default();
+        }
+        let Some(content) =
+            Self::path().and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            println!("Ignoring invalid clipboard history: {e}");
+            Self::default()
+        })
+    }
+
+    fn store(&self) {
+        if !PERSISTED.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let small = |text: &String| text.len() <= MAX_STORED_LEN;
+        let stored = Self {
+            history: self.history.iter().filter(|t| small(t)).cloned().collect(),
+            named: self
+                .named
+                .iter()
+                .filter(|(_, text)| small(text))
+                .map(|(name, text)| (name.clone(), text.clone()))
+                .collect(),
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(&stored)
+                    .map_err(std::io::Error::other)?;
+                std::fs::write(&path, json)
+            });
+        if let Err(e) = written {
+            println!("Unable to write clipboard history {:?}: {e}", path);
+        }
+    }
+}
+
+fn global() -> MutexGuard<'static, Clipboard> {
+    static CLIPBOARD: OnceLock<Mutex<Clipboard>> = OnceLock::new();
+    CLIPBOARD
+        .get_or_init(|| Mutex::new(Clipboard::load()))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+/// Keeps the clipboard in memory only, removing its file, or writes it
+/// again from now on.
+pub fn set_persisted(persisted: bool) {
+    let was = PERSISTED.swap(persisted, Ordering::Relaxed);
+    if persisted && !was {
+        global().store();
+    } else if !persisted {
+        if let Some(path) = Clipboard::path().filter(|p| p.exists()) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                println!("Unable to remove clipboard history {:?}: {e}", path);
+            }
+        }
+    }
+}
+
+pub fn current() -> Clipboard {
+    global().clone()
+}
+
+/// Adds a copy to the history, moving it to the front if already there.
+pub fn push(text: String) {
+    if text.is_empty() {
+        return;
+    }
+    let mut clipboard = global();
+    clipboard.history.retain(|t| *t != text);
+    clipboard.history.insert(0, text);
+    clipboard.history.truncate(HISTORY_LEN);
+    clipboard.store();
+}
+
+/// Whether `name` is a register, uppercase names appending to the
+/// lowercase one.
+pub fn is_register(name: char) -> bool {
+    name.is_ascii_alphabetic()
+}
+
+pub fn register(name: char) -> Option<String> {
+    global()
+        .named
+        .get(&name.to_ascii_lowercase().to_string())
+        .cloned()
+}
+
+/// Writes `text` to a register, or appends it with an uppercase name.
+/// Returns whether `name` is a register.
+pub fn set_register(name: char, text: String) -> bool {
+    if !is_register(name) {
+        return false;
+    }
+    let mut clipboard = global();
+    let entry = clipboard
+        .named
+        .entry(name.to_ascii_lowercase().to_string())
+        .or_default();
+    if name.is_ascii_uppercase() {
+        entry.push_str(&text);
+    } else {
+        *entry = text;
+    }
+    clipboard.store();
+    true
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Event;
+
+/// The menu of the menu bar a [`Command`] is listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuKind {
+    File,
+    Edit,
+    Selection,
+    View,
+    Go,
+    Help,
+}
+
+impl MenuKind {
+    pub const ALL: [Self; 6] = [
+        Self::File,
+        Self::Edit,
+        Self::Selection,
+        Self::View,
+        Self::Go,
+        Self::Help,
+    ];
+}
+
+impl fmt::Display for MenuKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// What a [`Command`] needs from the focused editor to be run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Requires {
+    #[default]
+    Nothing,
+    File,
+    Text,
+    Selection,
+    Markdown,
+    Image,
+}
+
+/// What the focused editor holds, sent by the editor through
+/// [`Event::ContextChanged`] so menus can grey out what can't be run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandContext {
+    pub file: bool,
+    pub text: bool,
+    pub selection: bool,
+    pub markdown: bool,
+    pub image: bool,
+}
+
+impl CommandContext {
+    pub fn allows(&self, requires: Requires) -> bool {
+        match requires {
+            Requires::Nothing => true,
+            Requires::File => self.file,
+            Requires::Text => self.text,
+            Requires::Selection => self.selection,
+            Requires::Markdown => self.markdown,
+            Requires::Image => self.image,
+        }
+    }
+}
+
+/// An action that can be run from the menus.
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub menu: MenuKind,
+    pub label: String,
+    pub event: Event,
+    pub requires: Requires,
+}
+
+impl Command {
+    pub fn new(menu: MenuKind, label: impl Into<String>, event: Event) -> Self {
+        Self {
+            menu,
+            label: label.into(),
+            event,
+            requires: Requires::Nothing,
+        }
+    }
+
+    pub fn requires(mut self, requires: Requires) -> Self {
+        self.requires = requires;
+        self
+    }
+
+    /// The key binding running the same event, if any.
+    pub fn shortcut(&self) -> Option<String> {
+        shortcut(&self.event)
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    commands: Vec<Command>,
+    /// Key bindings and the event they send.
+    shortcuts: Vec<(Event, String)>,
+}
+
+fn registry() -> MutexGuard<'static, Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY
+        .get_or_init(|| Mutex::new(Registry::default()))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+pub fn register(command: Command) {
+    registry().commands.push(command);
+}
+
+pub fn register_all(commands: impl IntoIterator<Item = Command>) {
+    registry().commands.extend(commands);
+}
+
+/// The registered commands of a menu, in the order they were registered.
+pub fn in_menu(menu: MenuKind) -> Vec<Command> {
+    registry()
+        .commands
+        .iter()
+        .filter(|c| c.menu == menu)
+        .cloned()
+        .collect()
+}
+
+/// Remembers the key binding sending `event`, the first one is kept.
+pub fn bind_shortcut(event: &Event, shortcut: String) {
+    let mut registry = registry();
+    if !registry.shortcuts.iter().any(|(bound, _)| bound == event) {
+        registry.shortcuts.push((event.clone(), shortcut));
+    }
+}
+
+/// The key binding sending `event`, the first one bound.
+pub fn shortcut(event: &Event) -> Option<String> {
+    registry()
+        .shortcuts
+        .iter()
+        .find(|(bound, _)| bound == event)
+        .map(|(_, shortcut)| shortcut.clone())
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a register is laid out in the window, see
+/// [`Register::dock`](crate::Register::dock).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Dock {
+    /// Across the window, above everything else, like the menu.
+    Top,
+    /// The sidebar left of the central area.
+    Left,
+    /// The central area, stacking its registers like the editor.
+    #[default]
+    Center,
+    /// The sidebar right of the central area.
+    Right,
+    /// The panel under the central area, between the sidebars.
+    Bottom,
+    /// Across the window, under everything else, like the status bar.
+    Footer,
+}
+
+impl Dock {
+    /// The docks that can be resized and collapsed.
+    pub const PANELS: [Self; 3] = [Self::Left, Self::Right, Self::Bottom];
+}
@@ -1,33 +1,229 @@
-use std::path::PathBuf;
-use iced::{highlighter, widget::{pane_grid, text_editor}};
+use std::{path::PathBuf, sync::Arc};
+use iced::{keyboard, widget::{pane_grid, text_editor}, window, Theme};
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    remote, CommandContext, Dock, Marker, PaneDrag, StatusItem, SyntaxTheme, Target
+};
 
-#[derive(Debug, Clone)]
+
+/// Everything registers tell each other. Events can be written out, see
+/// [`remote`] for the iced types they carry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Event {
     None,
+    /// An event for a register, pane or file only, see [`Target`].
+    To(Target, Box<Event>),
     Save,
-    Saved,
+    /// The buffer at the first path was written to the second one, with a
+    /// digest of what was written.
+    Saved(Option<(PathBuf, PathBuf, u64)>),
+    AutoSaveTick,
+    /// The buffers auto-save wrote, with a digest of what was written.
+    AutoSaved(Vec<(PathBuf, u64)>),
+    NewFile,
     OpenFile,
+    /// Open a file without asking for it, or show it if already open.
+    OpenPath(PathBuf),
+    OpenFolder,
+    FolderOpened(PathBuf),
+    /// Forget a recent file or folder.
+    RemoveRecent(PathBuf),
+    ToggleQuickOpen,
+    QuickOpenInput(String),
+    /// Open the first entry matching the quick-open input.
+    QuickOpenSubmit,
+    /// The files found under a workspace folder.
+    WorkspaceListed(PathBuf, Vec<PathBuf>),
+    /// A file dialog waits for the in-app file browser to answer.
+    ShowFileBrowser,
+    FileBrowserInput(String),
+    /// Go into the typed or selected folder, or answer with the file.
+    FileBrowserSubmit,
+    /// Go into the folder or answer with the file, as if clicked.
+    FileBrowserPick(PathBuf),
+    FileBrowserCancel,
     Opened(Option<(PathBuf, String)>),
+    OpenedBinary(PathBuf, Arc<Vec<u8>>),
     Quit(Option<usize>),
     Quited(PathBuf),
     RefreshEditorContent,
     ScanAllFiles,
     ScanFile(Option<PathBuf>),
-    EditorAction(text_editor::Action),
+    /// What a file holds, read again from disk.
+    Reloaded(PathBuf, String),
+    /// A file could not be read or written, with why.
+    FileError(PathBuf, String),
+    /// Hide the last file error.
+    ClearFileError,
+    EditorAction(#[serde(with = "remote::ActionDef")] text_editor::Action),
+    /// Scroll the focused editor from the first line to the second one.
+    MinimapScroll(usize, usize),
+    /// Replace the markers of every open buffer of the file, like the
+    /// search hits the editor marks for the selected text.
+    SetMarkers(PathBuf, Vec<Marker>),
+    /// What a file holds in the last commit of its git repository, `None`
+    /// when it isn't committed.
+    GitHead(PathBuf, Option<String>),
+    /// Scroll the focused editor so the line is at the top.
+    RevealLine(usize),
+    /// Convert the active buffer between `\n` and `\r\n` line endings.
+    ToggleLineEndings,
+    /// Read the git branch of the open files again.
+    RefreshBranch,
+    /// Replace every status bar item of the named register.
+    SetStatusItems(String, Vec<StatusItem>),
+    /// What the focused editor holds changed.
+    ContextChanged(CommandContext),
+    /// The focused editor shows another file, or none.
+    FocusChanged(Option<PathBuf>),
+    /// Ask for the text of an open buffer, answered by a `BufferRead`
+    /// addressed to the named register.
+    ReadBuffer(PathBuf, String),
+    BufferRead(PathBuf, Arc<String>),
+    /// Paste text at the cursor of the active buffer.
+    InsertText(String),
+    /// Replace the text of the active buffer, as one undo step.
+    ReplaceBuffer(String),
+    /// Bind a key released with the modifiers to an event.
+    BindKey(
+        char,
+        #[serde(with = "remote::modifiers")] keyboard::Modifiers,
+        Box<Event>,
+    ),
+    /// A command of a plugin, by the id it was registered with.
+    PluginCommand(String),
+    /// Run a function of the init script as a macro.
+    RunScript(String),
+    /// Start recording a keyboard macro under the name, or stop recording.
+    RecordMacro(Option<String>),
+    /// Play the named keyboard macro, or the last one recorded, the number
+    /// of times or to the end of the file.
+    PlayMacro(Option<String>, Option<usize>),
+    /// Play the next runs of the macro being played.
+    ResumeMacro,
+    /// Bind the next shortcut pressed to the named keyboard macro, or the
+    /// last one recorded, and save it with the macro.
+    BindMacroKey(Option<String>),
+    /// The shortcut pressed while binding a macro.
+    MacroKeyPressed(
+        char,
+        #[serde(with = "remote::modifiers")] keyboard::Modifiers,
+    ),
+    
+    Undo,
+    Redo,
+    /// Copy or cut the selection, keeping it in the clipboard history.
+    Copy,
+    Cut,
+    ToggleClipboard,
+    /// The register name typed in the clipboard panel.
+    ClipboardInput(String),
+    ClipboardSubmit,
+    /// Copy the selection to a named register, appending to it when the
+    /// name is uppercase.
+    CopyToRegister(char),
+    PasteRegister(char),
+    AddCursorAbove,
+    AddCursorBelow,
+    AddNextOccurrence,
+    SelectAllOccurrences,
+    ClearCursors,
+    ModifiersChanged(#[serde(with = "remote::modifiers")] keyboard::Modifiers),
+    
+    ImageZoomIn,
+    ImageZoomOut,
+    ImageActualSize,
+    ImageFit,
+    
+    /// Zoom the focused pane.
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    /// Zoom every pane, on top of their own zoom.
+    GlobalZoomIn,
+    GlobalZoomOut,
+    GlobalZoomReset,
+    
+    KeyPressed(
+        #[serde(with = "remote::key")] keyboard::Key,
+        #[serde(with = "remote::modifiers")] keyboard::Modifiers,
+    ),
+    HexSelect(usize),
+    HexScroll(f32),
+    HexGotoInput(String),
+    HexGoto,
+    HexPatternInput(String),
+    HexFindNext,
     
     TabSelected(usize),
     TabClosed(usize),
     NewTab,
+    NextTab,
+    PreviousTab,
     
-    PaneClicked(pane_grid::Pane),
-    PaneDragged(pane_grid::DragEvent),
-    PaneResized(pane_grid::ResizeEvent),
-    TogglePin(pane_grid::Pane),
-    Close(pane_grid::Pane),
-    Maximize(pane_grid::Pane),
+    /// Events about a pane carry its id, like [`Target::pane`].
+    PaneClicked(usize),
+    PaneDragged(PaneDrag),
+    /// Move the split numbered in layout order to the ratio.
+    PaneResized(usize, f32),
+    TogglePin(usize),
+    Close(usize),
+    Maximize(usize),
     Restore,
-    Split(pane_grid::Axis, pane_grid::Pane),
+    Split(#[serde(with = "remote::AxisDef")] pane_grid::Axis, usize),
+    TogglePreview(Option<usize>),
+    OpenLink(String),
+    
+    ThemeChanged(SyntaxTheme),
+    UiThemeChanged(#[serde(with = "remote::theme")] Theme),
+    /// A file of the themes directory changed.
+    ThemesDirChanged,
+    /// The themes directory changed and its themes were loaded again.
+    ThemesReloaded,
     
-    ThemeChanged(highlighter::Theme),
+    CloseRequested(#[serde(with = "remote::window_id")] window::Id),
+    WindowUnfocused,
+    
+    /// Collapse a sidebar or the bottom panel, or show it again.
+    ToggleDock(Dock),
+    /// Start dragging the edge of a dock, or stop with `None`.
+    DockDrag(Option<Dock>),
+    /// Set the width of a sidebar or the height of the bottom panel.
+    ResizeDock(Dock, f32),
+    
+    ToggleSettings,
+    SettingsSearch(String),
+    /// A setting being typed, by its `section.key`.
+    SettingsInput(String, String),
+    SettingsApply(String, String),
+    /// The settings file changed, maybe from outside Zen.
+    SettingsFileChanged,
+    /// The settings in use were replaced, read them again.
+    SettingsChanged,
+    
+    WriteSwaps,
+    RecoveryRestore(usize),
+    RecoveryDiscard(usize),
+    RecoveryDiff(usize),
+    /// A recovered buffer: its path, content and whether it was untitled.
+    Recovered(PathBuf, String, bool),
+}
+
+impl Event {
+    /// Whether ZenCore handles the event itself, for the whole window
+    /// rather than for a register or a pane.
+    pub fn is_app_wide(&self) -> bool {
+        matches!(
+            self,
+            Event::UiThemeChanged(_)
+                | Event::SettingsChanged
+                | Event::ThemesReloaded
+                | Event::CloseRequested(_)
+                | Event::ToggleDock(_)
+                | Event::DockDrag(_)
+                | Event::ResizeDock(..)
+        )
+    }
 }
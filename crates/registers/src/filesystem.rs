@@ -0,0 +1,257 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use iced::futures::{channel::mpsc, stream::BoxStream, StreamExt};
+
+/// How often [`LocalFs`] looks for changes to a watched path.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a [`FileSystem`] knows of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub is_dir: bool,
+    /// The size in bytes, `0` for a folder.
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub read_only: bool,
+}
+
+/// Where buffers, sessions and swap files are read from and written to.
+/// Handed to the editor so the local disk can be replaced, like in tests.
+/// Its calls block, so the editor makes them away from the UI thread.
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()>;
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// The entries of the folder at `path`, sorted.
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Creates the folder at `path` and the ones it is in.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Yields `path` each time it, or a file of the folder it names,
+    /// changes, until the stream is dropped.
+    fn watch(&self, path: &Path) -> BoxStream<'static, PathBuf>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|m| m.is_dir)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|m| !m.is_dir)
+    }
+}
+
+/// The disk of this machine.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalFs;
+
+impl FileSystem for LocalFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        std::fs::write(path, bytes)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Metadata {
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            read_only: metadata.permissions().readonly(),
+        })
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    /// Polls the modification times of `path` and of its entries from a
+    /// thread, which stops with the stream.
+    fn watch(&self, path: &Path) -> BoxStream<'static, PathBuf> {
+        let (sender, changes) = mpsc::unbounded();
+        let path = path.to_path_buf();
+        thread::spawn(move || {
+            let mut last = last_modified(&path);
+            while !sender.is_closed() {
+                thread::sleep(POLL_INTERVAL);
+                let now = last_modified(&path);
+                if now == last {
+                    continue;
+                }
+                last = now;
+                if sender.unbounded_send(path.clone()).is_err() {
+                    break;
+                }
+            }
+        });
+        changes.boxed()
+    }
+}
+
+/// Latest change to `path`, or to one of its entries when it is a folder.
+fn last_modified(path: &Path) -> Option<SystemTime> {
+    let modified = |path: &Path| std::fs::metadata(path)?.modified();
+    let entries = std::fs::read_dir(path).into_iter()
+        .flatten()
+        .filter_map(|entry| modified(&entry.ok()?.path()).ok());
+    entries.chain(modified(path).ok()).max()
+}
+
+#[derive(Debug, Default)]
+struct Memory {
+    files: BTreeMap<PathBuf, (Vec<u8>, SystemTime)>,
+    read_only: BTreeSet<PathBuf>,
+    watchers: Vec<(PathBuf, mpsc::UnboundedSender<PathBuf>)>,
+}
+
+impl Memory {
+    /// Tells the watchers of `path`, or of a folder holding it, that it
+    /// changed. Watchers whose stream was dropped are forgotten.
+    fn changed(&mut self, path: &Path) {
+        self.watchers.retain(|(watched, sender)| {
+            !path.starts_with(watched)
+                || sender.unbounded_send(watched.clone()).is_ok()
+        });
+    }
+}
+
+/// Files kept in memory, for tests. Folders are the ones holding a file.
+/// Clones share their files, so they can be looked at once the filesystem
+/// was handed to the editor.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    memory: Arc<Mutex<Memory>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file at `path`, as if written there.
+    pub fn with(self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        let path = path.into();
+        let _ = self.write(&path, &contents.into());
+        self
+    }
+
+    /// Makes the file at `path` refuse to be written.
+    pub fn with_read_only(self, path: impl Into<PathBuf>) -> Self {
+        self.memory().read_only.insert(path.into());
+        self
+    }
+
+    /// What the file at `path` holds, `None` when there is none.
+    pub fn contents(&self, path: &Path) -> Option<Vec<u8>> {
+        self.memory().files.get(path).map(|(bytes, _)| bytes.clone())
+    }
+
+    fn memory(&self) -> MutexGuard<'_, Memory> {
+        self.memory.lock().expect("Let's hope the lock isn't poisoned")
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{path:?} not found"))
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.contents(path).ok_or_else(|| Self::not_found(path))
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        let mut memory = self.memory();
+        if memory.read_only.contains(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{path:?} is read-only"),
+            ));
+        }
+        memory.files.insert(path.to_path_buf(), (bytes.to_vec(), SystemTime::now()));
+        memory.changed(path);
+        Ok(())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let memory = self.memory();
+        if let Some((bytes, modified)) = memory.files.get(path) {
+            return Ok(Metadata {
+                is_dir: false,
+                len: bytes.len() as u64,
+                modified: Some(*modified),
+                read_only: memory.read_only.contains(path),
+            });
+        }
+        if memory.files.keys().any(|file| file.starts_with(path)) {
+            return Ok(Metadata {
+                is_dir: true,
+                len: 0,
+                modified: None,
+                read_only: false,
+            });
+        }
+        Err(Self::not_found(path))
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let entries: BTreeSet<PathBuf> = self.memory().files.keys()
+            .filter_map(|file| file.strip_prefix(path).ok()?.components().next())
+            .map(|first| path.join(first))
+            .collect();
+        if entries.is_empty() {
+            return Err(Self::not_found(path));
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    /// Folders are the ones holding a file, so there is nothing to do.
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut memory = self.memory();
+        memory.files.remove(path).ok_or_else(|| Self::not_found(path))?;
+        memory.changed(path);
+        Ok(())
+    }
+
+    fn watch(&self, path: &Path) -> BoxStream<'static, PathBuf> {
+        let (sender, changes) = mpsc::unbounded();
+        self.memory().watchers.push((path.to_path_buf(), sender));
+        changes.boxed()
+    }
+}
@@ -1,5 +1,35 @@
 pub mod registers;
-pub use registers::Register;
+pub use registers::{AsAny, Register};
+
+pub mod dock;
+pub use dock::Dock;
 
 pub mod event;
 pub use event::Event;
+
+pub mod pane;
+pub use pane::{DropTarget, PaneDrag};
+
+pub mod marker;
+pub use marker::{Marker, MarkerKind};
+
+pub mod theme;
+pub use theme::SyntaxTheme;
+
+pub mod status;
+pub use status::{StatusItem, StatusSide};
+
+pub mod commands;
+pub use commands::{Command, CommandContext, MenuKind, Requires};
+
+pub mod target;
+pub use target::Target;
+
+pub mod clipboard;
+
+pub mod filesystem;
+pub use filesystem::{FileSystem, LocalFs, MemoryFs};
+
+pub mod paths;
+
+pub mod remote;
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// The kind of a [`Marker`], which decides how it is painted in overlays
+/// such as the minimap. The editor marks search hits and changes since
+/// the last commit; diagnostics are left to the registers checking files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    SearchHit,
+    Error,
+    Warning,
+    GitAdded,
+    GitModified,
+    GitRemoved,
+}
+
+impl MarkerKind {
+    /// Whether it marks a change since the last commit.
+    pub fn is_git(self) -> bool {
+        matches!(self, Self::GitAdded | Self::GitModified | Self::GitRemoved)
+    }
+}
+
+/// A line annotation attached to a file by any register (search, 
+/// diagnostics, version control...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    pub line: usize,
+    pub kind: MarkerKind,
+}
+
+impl Marker {
+    pub fn new(line: usize, kind: MarkerKind) -> Self {
+        Self { line, kind }
+    }
+}
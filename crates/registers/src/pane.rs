@@ -0,0 +1,51 @@
+//! What the editor panes tell each other, by the id the editor gives a
+//! pane instead of the one of the `pane_grid`, so the events mean the same
+//! once replayed.
+
+use iced::widget::pane_grid::{Edge, Region};
+use serde::{Deserialize, Serialize};
+
+use crate::remote;
+
+/// Where a dragged pane is dropped.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum DropTarget {
+    /// An edge of the whole grid.
+    Edge(#[serde(with = "remote::EdgeDef")] Edge),
+    /// A region of the pane with the id.
+    Pane(usize, #[serde(with = "remote::RegionDef")] Region),
+}
+
+// The `pane_grid` types aren't comparable
+fn same_edge(a: Edge, b: Edge) -> bool {
+    matches!(
+        (a, b),
+        (Edge::Top, Edge::Top)
+            | (Edge::Left, Edge::Left)
+            | (Edge::Right, Edge::Right)
+            | (Edge::Bottom, Edge::Bottom)
+    )
+}
+
+impl PartialEq for DropTarget {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Edge(a), Self::Edge(b)) => same_edge(*a, *b),
+            (Self::Pane(a, Region::Center), Self::Pane(b, Region::Center)) => a == b,
+            (Self::Pane(a, Region::Edge(x)), Self::Pane(b, Region::Edge(y))) => {
+                a == b && same_edge(*x, *y)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DropTarget {}
+
+/// A pane, by its id, being dragged over the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaneDrag {
+    Picked(usize),
+    Dropped(usize, DropTarget),
+    Canceled(usize),
+}
@@ -0,0 +1,38 @@
+//! Where Zen keeps its files: the configuration it reads, like the
+//! settings and themes, and the data it writes, like the session and the
+//! swap files. Both are a `zen` folder of the directories of the system,
+//! unless redirected to keep replays and tests away from them.
+
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+fn root() -> MutexGuard<'static, Option<PathBuf>> {
+    static ROOT: OnceLock<Mutex<Option<PathBuf>>> = OnceLock::new();
+    ROOT.get_or_init(|| Mutex::new(None))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+/// Makes [`config_dir`] and [`data_dir`] the `config` and `data` folders
+/// of `root` from now on. Files already loaded stay as they were read.
+pub fn redirect(root_dir: impl Into<PathBuf>) {
+    *root() = Some(root_dir.into());
+}
+
+/// The folder of the settings, themes, plugins and init script.
+pub fn config_dir() -> Option<PathBuf> {
+    match root().as_ref() {
+        Some(root) => Some(root.join("config")),
+        None => Some(dirs::config_dir()?.join("zen")),
+    }
+}
+
+/// The folder of the session, swap files, history and other state.
+pub fn data_dir() -> Option<PathBuf> {
+    match root().as_ref() {
+        Some(root) => Some(root.join("data")),
+        None => Some(dirs::data_dir()?.join("zen")),
+    }
+}
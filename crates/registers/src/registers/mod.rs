@@ -1,12 +1,38 @@
 
+use std::any::Any;
+
 use iced::{widget::Column, Element, Subscription, Task, Theme};
-use crate::Event;
+use crate::{Dock, Event, Target};
+
+/// Lets a boxed register be looked up by its type. Call it on the register,
+/// not on the `Box` holding it, which is `Any` too.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
 
-pub trait Register {
+pub trait Register: AsAny {
     fn update(&mut self, _: Event) -> Task<Event> {
         Task::none()
     }
     
+    /// Handles an event addressed to this register. The `target` no longer
+    /// names the register, registers holding panes or files use the rest of
+    /// it to pick the ones that get the event.
+    fn route(&mut self, _target: Target, event: Event) -> Task<Event> {
+        self.update(event)
+    }
+    
     fn view(&self) -> Element<'_, Event> {
         Column::new().into()
     }
@@ -19,8 +45,24 @@ pub trait Register {
         String::from("Register")
     }
     
+    /// Identifies the register in addressed events.
+    fn name(&self) -> String {
+        self.title()
+    }
+    
     fn theme(&self) -> Theme {
         Theme::Dracula
     }
+    
+    /// Where the register is laid out.
+    fn dock(&self) -> Dock {
+        Dock::Center
+    }
+    
+    /// Whether the register shows anything. A sidebar or bottom panel with
+    /// no open register takes no room.
+    fn is_open(&self) -> bool {
+        true
+    }
 
 }
@@ -0,0 +1,216 @@
+//! Serde for the iced types events carry, so an [`Event`](crate::Event)
+//! can be recorded and replayed. Used with `#[serde(with = "...")]`.
+
+use std::sync::Arc;
+
+use iced::{
+    keyboard::{self, key::Named},
+    theme::Palette,
+    widget::{
+        pane_grid::{Axis, Edge, Region},
+        text_editor::{Action, Edit, Motion},
+    },
+    window, Color, Point, Theme,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Motion")]
+enum MotionDef {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    DocumentStart,
+    DocumentEnd,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Edit")]
+enum EditDef {
+    Insert(char),
+    Paste(Arc<String>),
+    Enter,
+    Backspace,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Point")]
+struct PointDef {
+    x: f32,
+    y: f32,
+}
+
+/// A `text_editor::Action`. Clicks and drags keep the position they had in
+/// the recorded layout.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Action")]
+pub enum ActionDef {
+    Move(#[serde(with = "MotionDef")] Motion),
+    Select(#[serde(with = "MotionDef")] Motion),
+    SelectWord,
+    SelectLine,
+    SelectAll,
+    Edit(#[serde(with = "EditDef")] Edit),
+    Click(#[serde(with = "PointDef")] Point),
+    Drag(#[serde(with = "PointDef")] Point),
+    Scroll { lines: i32 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Axis")]
+pub enum AxisDef {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Edge")]
+pub enum EdgeDef {
+    Top,
+    Left,
+    Right,
+    Bottom,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Region")]
+pub enum RegionDef {
+    Center,
+    Edge(#[serde(with = "EdgeDef")] Edge),
+}
+
+pub mod modifiers {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        modifiers: &keyboard::Modifiers,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        modifiers.bits().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<keyboard::Modifiers, D::Error> {
+        Ok(keyboard::Modifiers::from_bits_truncate(u32::deserialize(d)?))
+    }
+}
+
+/// The named keys views react to, other ones are replayed as unidentified.
+const NAMED: [Named; 15] = [
+    Named::ArrowUp,
+    Named::ArrowDown,
+    Named::ArrowLeft,
+    Named::ArrowRight,
+    Named::Home,
+    Named::End,
+    Named::PageUp,
+    Named::PageDown,
+    Named::Enter,
+    Named::Tab,
+    Named::Space,
+    Named::Escape,
+    Named::Backspace,
+    Named::Delete,
+    Named::Insert,
+];
+
+#[derive(Serialize, Deserialize)]
+enum KeyDef {
+    Named(String),
+    Character(String),
+    Unidentified,
+}
+
+pub mod key {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(key: &keyboard::Key, s: S) -> Result<S::Ok, S::Error> {
+        match key {
+            keyboard::Key::Named(named) => KeyDef::Named(format!("{named:?}")),
+            keyboard::Key::Character(c) => KeyDef::Character(c.to_string()),
+            keyboard::Key::Unidentified => KeyDef::Unidentified,
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<keyboard::Key, D::Error> {
+        Ok(match KeyDef::deserialize(d)? {
+            KeyDef::Named(name) => NAMED
+                .into_iter()
+                .find(|named| format!("{named:?}") == name)
+                .map_or(keyboard::Key::Unidentified, keyboard::Key::Named),
+            KeyDef::Character(c) => keyboard::Key::Character(c.as_str().into()),
+            KeyDef::Unidentified => keyboard::Key::Unidentified,
+        })
+    }
+}
+
+/// A UI theme by its name, with its palette for the custom ones.
+#[derive(Serialize, Deserialize)]
+struct ThemeDef {
+    name: String,
+    background: [f32; 4],
+    text: [f32; 4],
+    primary: [f32; 4],
+    success: [f32; 4],
+    danger: [f32; 4],
+}
+
+pub mod theme {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(theme: &Theme, s: S) -> Result<S::Ok, S::Error> {
+        let palette = theme.palette();
+        let rgba = |c: Color| [c.r, c.g, c.b, c.a];
+        ThemeDef {
+            name: theme.to_string(),
+            background: rgba(palette.background),
+            text: rgba(palette.text),
+            primary: rgba(palette.primary),
+            success: rgba(palette.success),
+            danger: rgba(palette.danger),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Theme, D::Error> {
+        let def = ThemeDef::deserialize(d)?;
+        let built_in = Theme::ALL.iter().find(|t| t.to_string() == def.name);
+        Ok(built_in.cloned().unwrap_or_else(|| {
+            let color = |[r, g, b, a]: [f32; 4]| Color::from_rgba(r, g, b, a);
+            Theme::custom(
+                def.name,
+                Palette {
+                    background: color(def.background),
+                    text: color(def.text),
+                    primary: color(def.primary),
+                    success: color(def.success),
+                    danger: color(def.danger),
+                },
+            )
+        }))
+    }
+}
+
+/// Window ids can't be made again, a replayed one is a new id.
+pub mod window_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(_id: &window::Id, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_unit()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<window::Id, D::Error> {
+        <()>::deserialize(d)?;
+        Ok(window::Id::unique())
+    }
+}
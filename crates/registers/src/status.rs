@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Event;
+
+/// Where a [`StatusItem`] is placed in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusSide {
+    Left,
+    Right,
+}
+
+/// A piece of the status bar. Any register can contribute items through
+/// [`Event::SetStatusItems`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusItem {
+    pub side: StatusSide,
+    pub text: String,
+    /// Run when the item is clicked, items without one are plain text.
+    pub command: Option<Box<Event>>,
+}
+
+impl StatusItem {
+    pub fn left(text: impl Into<String>) -> Self {
+        Self {
+            side: StatusSide::Left,
+            text: text.into(),
+            command: None,
+        }
+    }
+
+    pub fn right(text: impl Into<String>) -> Self {
+        Self {
+            side: StatusSide::Right,
+            ..Self::left(text)
+        }
+    }
+
+    pub fn on_click(mut self, command: Event) -> Self {
+        self.command = Some(Box::new(command));
+        self
+    }
+}
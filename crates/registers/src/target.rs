@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Event;
+
+/// Who an addressed event is meant for, see [`Event::To`]. Every part left
+/// out matches everything, so the default target is a broadcast.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    /// The [`Register::name`](crate::Register::name) of the receiver.
+    pub register: Option<String>,
+    /// The id of an editor pane.
+    pub pane: Option<usize>,
+    /// An open file, which doesn't need to be the active one.
+    pub file: Option<PathBuf>,
+}
+
+impl Target {
+    /// Every register and, for the editor, every pane instead of the
+    /// focused one.
+    pub fn broadcast() -> Self {
+        Self::default()
+    }
+
+    pub fn register(name: impl Into<String>) -> Self {
+        Self {
+            register: Some(name.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn pane(id: usize) -> Self {
+        Self {
+            pane: Some(id),
+            ..Self::default()
+        }
+    }
+
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn in_pane(mut self, id: usize) -> Self {
+        self.pane = Some(id);
+        self
+    }
+
+    pub fn of_register(mut self, name: impl Into<String>) -> Self {
+        self.register = Some(name.into());
+        self
+    }
+
+    /// Wraps `event` in an envelope for this target.
+    pub fn send(self, event: Event) -> Event {
+        Event::To(self, Box::new(event))
+    }
+
+    /// Whether a register with this name is addressed.
+    pub fn reaches(&self, name: &str) -> bool {
+        self.register.as_deref().is_none_or(|r| r == name)
+    }
+
+    /// The target once it reached its register.
+    pub fn within_register(self) -> Self {
+        Self {
+            register: None,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use iced::highlighter;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A syntax highlighting theme, either one shipped with iced or one loaded
+/// from the themes directory by its name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxTheme {
+    BuiltIn(highlighter::Theme),
+    Custom(String),
+}
+
+impl SyntaxTheme {
+    /// The built-in theme with this name, or a custom one otherwise.
+    pub fn named(name: &str) -> Self {
+        highlighter::Theme::ALL
+            .iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(name))
+            .map_or_else(
+                || Self::Custom(name.to_string()),
+                |t| Self::BuiltIn(*t),
+            )
+    }
+}
+
+impl Default for SyntaxTheme {
+    fn default() -> Self {
+        Self::BuiltIn(highlighter::Theme::Base16Ocean)
+    }
+}
+
+impl From<highlighter::Theme> for SyntaxTheme {
+    fn from(theme: highlighter::Theme) -> Self {
+        Self::BuiltIn(theme)
+    }
+}
+
+/// Written by name, built-in themes coming first when read back.
+impl Serialize for SyntaxTheme {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SyntaxTheme {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Ok(Self::named(&String::deserialize(d)?))
+    }
+}
+
+impl fmt::Display for SyntaxTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuiltIn(theme) => theme.fmt(f),
+            Self::Custom(name) => f.write_str(name),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
+
+use iced::keyboard::Modifiers;
+use registers::{commands, Command, Dock, Event, MenuKind};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, INT};
+
+use crate::ScriptBuffer;
+
+/// What a script did, read back once it returns.
+#[derive(Debug, Default)]
+pub struct Run {
+    /// The buffer of the focused editor, `None` for the init script.
+    pub buffer: Option<ScriptBuffer>,
+    pub events: Vec<Event>,
+}
+
+pub fn run() -> MutexGuard<'static, Run> {
+    static RUN: Mutex<Run> = Mutex::new(Run {
+        buffer: None,
+        events: Vec::new(),
+    });
+    RUN.lock().expect("Let's hope the lock isn't poisoned")
+}
+
+type Fallible<T> = Result<T, Box<EvalAltResult>>;
+
+fn emit(event: Event) {
+    run().events.push(event);
+}
+
+/// Runs `f` on the buffer, failing the script outside of an editor.
+fn with_buffer<T>(f: impl FnOnce(&mut ScriptBuffer) -> T) -> Fallible<T> {
+    match run().buffer.as_mut() {
+        Some(buffer) => Ok(f(buffer)),
+        None => Err("No buffer to edit outside of a macro".into()),
+    }
+}
+
+fn index(value: INT) -> usize {
+    usize::try_from(value).unwrap_or(0)
+}
+
+/// The functions scripts can call.
+pub fn register(engine: &mut Engine) {
+    engine.register_fn("command", |menu: &str, label: &str, function: &str| {
+        let menu = MenuKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(menu))
+            .ok_or_else(|| format!("Unknown menu {menu}"))?;
+        commands::register(Command::new(
+            menu,
+            label,
+            Event::RunScript(function.to_string()),
+        ));
+        Fallible::Ok(())
+    });
+    engine.register_fn("bind", |key: &str, modifiers: &str, function: &str| {
+        bind(key, modifiers, Event::RunScript(function.to_string()))
+    });
+    engine.register_fn("emit", |name: &str| {
+        let event = event_named(name).ok_or_else(|| format!("Unknown event {name}"))?;
+        emit(event);
+        Fallible::Ok(())
+    });
+    engine.register_fn("run", |function: &str| {
+        emit(Event::RunScript(function.to_string()));
+    });
+    engine.register_fn("record_macro", |name: &str| {
+        emit(Event::RecordMacro(Some(name.to_string())));
+    });
+    engine.register_fn("play_macro", |name: &str, times: INT| {
+        emit(Event::PlayMacro(Some(name.to_string()), Some(index(times))));
+    });
+    engine.register_fn("play_macro_to_end", |name: &str| {
+        emit(Event::PlayMacro(Some(name.to_string()), None));
+    });
+    engine.register_fn("bind_macro", |key: &str, modifiers: &str, name: &str| {
+        bind(key, modifiers, Event::PlayMacro(Some(name.to_string()), Some(1)))
+    });
+    engine.register_fn("open", |path: &str| emit(Event::OpenPath(PathBuf::from(path))));
+    engine.register_fn("open_link", |url: &str| emit(Event::OpenLink(url.to_string())));
+    engine.register_fn("reveal_line", |line: INT| emit(Event::RevealLine(index(line))));
+
+    engine.register_fn("text", || with_buffer(|b| b.text.clone()));
+    engine.register_fn("set_text", |text: &str| {
+        with_buffer(|b| b.set_text(text.to_string()))
+    });
+    engine.register_fn("selection", || with_buffer(|b| b.selection().to_string()));
+    engine.register_fn("insert", |text: &str| with_buffer(|b| b.insert(text)));
+    engine.register_fn("line_count", || with_buffer(|b| b.line_count() as INT));
+    engine.register_fn("line", |line: INT| {
+        with_buffer(|b| b.line(index(line)).unwrap_or_default().to_string())
+    });
+    engine.register_fn("cursor", || {
+        with_buffer(|b| {
+            let (line, column) = b.position(b.head);
+            Array::from([Dynamic::from(line as INT), Dynamic::from(column as INT)])
+        })
+    });
+    engine.register_fn(
+        "select",
+        |line: INT, column: INT, to_line: INT, to_column: INT| {
+            with_buffer(|b| {
+                b.anchor = b.offset(index(line), index(column));
+                b.head = b.offset(index(to_line), index(to_column));
+            })
+        },
+    );
+    engine.register_fn("select_all", || {
+        with_buffer(|b| {
+            b.anchor = 0;
+            b.head = b.text.len();
+        })
+    });
+}
+
+/// Binds a single character `key` with `modifiers` to `event`.
+fn bind(key: &str, modifiers: &str, event: Event) -> Fallible<()> {
+    let mut keys = key.chars();
+    let (Some(key), None) = (keys.next(), keys.next()) else {
+        return Err(format!("Keys are a single character, not {key}").into());
+    };
+    let modifiers = parse_modifiers(modifiers)?;
+    emit(Event::BindKey(key.to_ascii_lowercase(), modifiers, Box::new(event)));
+    Ok(())
+}
+
+/// Parses modifiers like `ctrl+shift`, empty for none.
+fn parse_modifiers(modifiers: &str) -> Result<Modifiers, String> {
+    modifiers
+        .split('+')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .try_fold(Modifiers::empty(), |held, m| {
+            let modifier = match m.to_lowercase().as_str() {
+                "ctrl" | "cmd" => Modifiers::CTRL,
+                "shift" => Modifiers::SHIFT,
+                "alt" => Modifiers::ALT,
+                _ => return Err(format!("Unknown modifier {m}")),
+            };
+            Ok(held | modifier)
+        })
+}
+
+/// The events without arguments scripts emit by name. The ones taking
+/// arguments have functions of their own, like `open`.
+fn event_named(name: &str) -> Option<Event> {
+    Some(match name {
+        "Save" => Event::Save,
+        "NewFile" => Event::NewFile,
+        "OpenFile" => Event::OpenFile,
+        "OpenFolder" => Event::OpenFolder,
+        "CloseFile" => Event::Quit(None),
+        "ReloadFile" => Event::ScanFile(None),
+        "ReloadAllFiles" => Event::ScanAllFiles,
+        "ToggleQuickOpen" => Event::ToggleQuickOpen,
+        "ToggleSettings" => Event::ToggleSettings,
+        "TogglePreview" => Event::TogglePreview(None),
+        "ToggleLineEndings" => Event::ToggleLineEndings,
+        "Undo" => Event::Undo,
+        "Redo" => Event::Redo,
+        "Copy" => Event::Copy,
+        "Cut" => Event::Cut,
+        "ToggleClipboard" => Event::ToggleClipboard,
+        "ToggleLeftSidebar" => Event::ToggleDock(Dock::Left),
+        "ToggleRightSidebar" => Event::ToggleDock(Dock::Right),
+        "ToggleBottomPanel" => Event::ToggleDock(Dock::Bottom),
+        "AddCursorAbove" => Event::AddCursorAbove,
+        "AddCursorBelow" => Event::AddCursorBelow,
+        "AddNextOccurrence" => Event::AddNextOccurrence,
+        "SelectAllOccurrences" => Event::SelectAllOccurrences,
+        "ClearCursors" => Event::ClearCursors,
+        "NewTab" => Event::NewTab,
+        "NextTab" => Event::NextTab,
+        "PreviousTab" => Event::PreviousTab,
+        "Restore" => Event::Restore,
+        "ZoomIn" => Event::ZoomIn,
+        "ZoomOut" => Event::ZoomOut,
+        "ZoomReset" => Event::ZoomReset,
+        "GlobalZoomIn" => Event::GlobalZoomIn,
+        "GlobalZoomOut" => Event::GlobalZoomOut,
+        "GlobalZoomReset" => Event::GlobalZoomReset,
+        "ImageZoomIn" => Event::ImageZoomIn,
+        "ImageZoomOut" => Event::ImageZoomOut,
+        "ImageActualSize" => Event::ImageActualSize,
+        "ImageFit" => Event::ImageFit,
+        _ => return None,
+    })
+}
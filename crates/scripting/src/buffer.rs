@@ -0,0 +1,81 @@
+/// The text a macro edits and its selection, as byte offsets into `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptBuffer {
+    pub text: String,
+    pub anchor: usize,
+    pub head: usize,
+}
+
+impl ScriptBuffer {
+    pub fn new(text: String, anchor: usize, head: usize) -> Self {
+        Self { text, anchor, head }
+    }
+
+    pub fn start(&self) -> usize {
+        self.anchor.min(self.head)
+    }
+
+    pub fn end(&self) -> usize {
+        self.anchor.max(self.head)
+    }
+
+    pub fn selection(&self) -> &str {
+        &self.text[self.start()..self.end()]
+    }
+
+    /// Replaces the selection with `text`, leaving the caret after it.
+    pub fn insert(&mut self, text: &str) {
+        let start = self.start();
+        self.text.replace_range(start..self.end(), text);
+        self.anchor = start + text.len();
+        self.head = self.anchor;
+    }
+
+    /// Replaces the whole text, keeping the selection where it still fits.
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.anchor = self.clamp(self.anchor);
+        self.head = self.clamp(self.head);
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.split('\n').count()
+    }
+
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.text.split('\n').nth(line)
+    }
+
+    /// The line and character column of a byte offset.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.text[..self.clamp(offset)];
+        let line = before.matches('\n').count();
+        let start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[start..].chars().count())
+    }
+
+    /// The byte offset of a line and character column, clamped to the text.
+    pub fn offset(&self, line: usize, column: usize) -> usize {
+        let mut start = 0;
+        for _ in 0..line {
+            match self.text[start..].find('\n') {
+                Some(i) => start += i + 1,
+                None => return self.text.len(),
+            }
+        }
+        let text = &self.text[start..];
+        let end = text.find('\n').unwrap_or(text.len());
+        start + text[..end]
+            .char_indices()
+            .nth(column)
+            .map_or(end, |(i, _)| i)
+    }
+
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
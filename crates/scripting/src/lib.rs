@@ -0,0 +1,125 @@
+//! User scripts written in [Rhai](https://rhai.rs).
+//!
+//! The `init.rhai` script of the config directory runs at startup. It can
+//! add commands and key bindings running its functions as macros:
+//!
+//! ```rhai
+//! command("Edit", "Upper Case", "upper");
+//! bind("u", "ctrl+shift", "upper");
+//!
+//! fn upper() {
+//!     insert(selection().to_upper());
+//! }
+//! ```
+//!
+//! - `command(menu, label, function)` and `bind(key, modifiers, function)`
+//!   run `function` from a menu or a key
+//! - `emit(name)` sends an event without arguments, like `"Save"`, and
+//!   `open(path)`, `open_link(url)`, `reveal_line(line)` and `run(function)`
+//!   the ones taking some
+//! - `record_macro(name)`, `play_macro(name, times)`,
+//!   `play_macro_to_end(name)` and `bind_macro(key, modifiers, name)` for
+//!   keyboard macros
+//!
+//! Macros also edit the buffer of the focused editor, with lines and
+//! columns counted in characters from zero:
+//!
+//! - `text()`, `set_text(text)`, `line(line)` and `line_count()`
+//! - `selection()` and `insert(text)`, which replaces the selection
+//! - `cursor()`, `select(line, column, to_line, to_column)` and
+//!   `select_all()`
+
+mod api;
+mod buffer;
+pub use buffer::ScriptBuffer;
+
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use registers::Event;
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+
+/// Operations a script may run before it is stopped, so a looping one
+/// can't hold up the editor.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+struct Scripts {
+    engine: Engine,
+    /// The init script, its functions being the macros.
+    init: Option<AST>,
+}
+
+fn scripts() -> MutexGuard<'static, Scripts> {
+    static SCRIPTS: OnceLock<Mutex<Scripts>> = OnceLock::new();
+    SCRIPTS
+        .get_or_init(|| {
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            api::register(&mut engine);
+            Mutex::new(Scripts { engine, init: None })
+        })
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+pub fn init_script_path() -> Option<PathBuf> {
+    Some(registers::paths::config_dir()?.join("init.rhai"))
+}
+
+/// Runs the init script, keeping its functions as macros. Returns the
+/// events it sent, none without a script.
+pub fn init() -> Vec<Event> {
+    let Some(source) = init_script_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    let mut scripts = scripts();
+    let ast = match scripts.engine.compile(source) {
+        Ok(ast) => ast,
+        Err(e) => {
+            println!("Unable to compile the init script: {e}");
+            return Vec::new();
+        }
+    };
+    *api::run() = api::Run::default();
+    if let Err(e) = scripts.engine.run_ast(&ast) {
+        println!("The init script failed: {e}");
+    }
+    scripts.init = Some(ast);
+    std::mem::take(&mut api::run().events)
+}
+
+/// Runs the macro `function` of the init script on `buffer`, the focused
+/// editor's one if any. Returns the buffer as the macro left it and the
+/// events it sent.
+pub fn run_macro(
+    function: &str,
+    buffer: Option<ScriptBuffer>,
+) -> (Option<ScriptBuffer>, Vec<Event>) {
+    let scripts = scripts();
+    let Some(ast) = &scripts.init else {
+        println!("No init script defines {function}");
+        return (buffer, Vec::new());
+    };
+    *api::run() = api::Run {
+        buffer: buffer.clone(),
+        events: Vec::new(),
+    };
+    // Without running the top level again, which adds the commands
+    let options = CallFnOptions::new().eval_ast(false);
+    let result = scripts.engine.call_fn_with_options::<Dynamic>(
+        options, &mut Scope::new(), ast, function, ()
+    );
+    let run = std::mem::take(&mut *api::run());
+    match result {
+        Ok(_) => (run.buffer, run.events),
+        // A failed macro changes nothing
+        Err(e) => {
+            println!("Macro {function} failed: {e}");
+            (buffer, Vec::new())
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
+
+use iced::{font, Task};
+use registers::Event;
+
+/// Loads a font file into iced, so its family can be used by name. Every
+/// file is only loaded once, later calls with the same path do nothing.
+pub fn load_file(path: &str) -> Task<Event> {
+    static LOADED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    if path.trim().is_empty() {
+        return Task::none();
+    }
+    let path = PathBuf::from(path.trim());
+    let mut loaded = LOADED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned");
+    if loaded.contains(&path) {
+        return Task::none();
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            loaded.insert(path);
+            font::load(bytes).map(|_| Event::None)
+        }
+        Err(e) => {
+            println!("Unable to load font {:?}: {e}", path);
+            Task::none()
+        }
+    }
+}
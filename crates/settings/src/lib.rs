@@ -0,0 +1,12 @@
+pub mod model;
+pub use model::*;
+
+pub mod fonts;
+
+pub mod recent;
+
+pub mod store;
+pub use store::current;
+
+pub mod settings_editor;
+pub use settings_editor::SettingsEditor;
@@ -0,0 +1,192 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Mutex, OnceLock},
+};
+
+use iced::{highlighter, Font, Theme};
+use registers::SyntaxTheme;
+use serde::{Deserialize, Serialize};
+
+/// Everything a user can configure, as read from the settings file.
+/// Missing keys take their default value.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub ui: UiSettings,
+    pub editor: EditorSettings,
+    pub auto_save: AutoSaveSettings,
+    pub clipboard: ClipboardSettings,
+    /// Editor overrides by file extension, like `[languages.md]`.
+    pub languages: BTreeMap<String, LanguageSettings>,
+    /// What each plugin may do, like `my-plugin = ["commands"]`. Plugins
+    /// only get what they ask for and are granted here.
+    pub plugins: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub title: String,
+    /// The name of a built-in iced theme or of a palette in the themes
+    /// directory.
+    pub theme: String,
+    pub padding: f32,
+    pub spacing: f32,
+    /// Whether files are picked with the dialogs of the system, or browsed
+    /// from the keyboard inside Zen. Read when Zen starts.
+    pub native_dialogs: bool,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            title: String::from("Zen Editor (v1)"),
+            theme: Theme::Dark.to_string(),
+            padding: 5.0,
+            spacing: 5.0,
+            native_dialogs: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    pub font_size: f32,
+    /// `monospace`, `default`, the name of an installed font or the family
+    /// of `font_file`.
+    pub font_family: String,
+    /// A `.ttf` or `.otf` file loaded at startup, empty for none.
+    pub font_file: String,
+    /// Line height, relative to the font size.
+    pub line_height: f32,
+    pub padding: f32,
+    /// The syntax theme of panes opened without a session, built-in or
+    /// from the themes directory.
+    pub theme: String,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            font_size: 16.0,
+            font_family: String::from("monospace"),
+            font_file: String::new(),
+            line_height: 1.3,
+            padding: 5.0,
+            theme: highlighter::Theme::Base16Ocean.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoSaveSettings {
+    /// Milliseconds without edits before saving, `0` disables it.
+    pub delay_ms: u64,
+    pub on_focus_loss: bool,
+    pub on_switch: bool,
+}
+
+impl Default for AutoSaveSettings {
+    fn default() -> Self {
+        Self {
+            delay_ms: 0,
+            on_focus_loss: true,
+            on_switch: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// Whether the clipboard history and registers are kept in a file for
+    /// the next sessions. The file is plain text, so turning this off keeps
+    /// copied secrets in memory only.
+    pub persist: bool,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self { persist: true }
+    }
+}
+
+/// The editor settings a language replaces, the rest are inherited.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageSettings {
+    pub font_size: Option<f32>,
+    pub font_family: Option<String>,
+    pub line_height: Option<f32>,
+    pub padding: Option<f32>,
+}
+
+impl Settings {
+    /// The editor settings of files with the `language` extension.
+    pub fn editor_for(&self, language: &str) -> EditorSettings {
+        let mut editor = self.editor.clone();
+        if let Some(lang) = self.languages.get(&language.to_lowercase()) {
+            editor.font_size = lang.font_size.unwrap_or(editor.font_size);
+            editor.line_height = lang.line_height.unwrap_or(editor.line_height);
+            editor.padding = lang.padding.unwrap_or(editor.padding);
+            if let Some(family) = &lang.font_family {
+                editor.font_family = family.clone();
+            }
+        }
+        editor
+    }
+
+    /// The UI theme, `None` when no theme has that name.
+    pub fn ui_theme(&self) -> Option<Theme> {
+        ui::themes::ui_theme(&self.ui.theme)
+    }
+}
+
+impl EditorSettings {
+    pub fn syntax_theme(&self) -> SyntaxTheme {
+        SyntaxTheme::named(&self.theme)
+    }
+
+    pub fn font(&self) -> Font {
+        match self.font_family.to_lowercase().as_str() {
+            "monospace" => Font::MONOSPACE,
+            "default" | "" => Font::DEFAULT,
+            _ => Font::with_name(intern(&self.font_family)),
+        }
+    }
+
+    /// These settings with the font and padding scaled by `factor`.
+    pub fn zoomed(mut self, factor: f32) -> Self {
+        self.font_size *= factor;
+        self.padding *= factor;
+        self
+    }
+
+    pub fn line_height_px(&self) -> f32 {
+        self.font_size * self.line_height
+    }
+
+    /// Advance of a monospace glyph, used to map points to columns.
+    pub fn char_width(&self) -> f32 {
+        self.font_size * 0.6
+    }
+}
+
+/// Font names must be `'static`, so each family is leaked once.
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned");
+    match names.get(name) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.into());
+            names.insert(interned);
+            interned
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::SystemTime,
+};
+
+use registers::FileSystem;
+use serde::{Deserialize, Serialize};
+
+const MAX_FILES: usize = 20;
+const MAX_FOLDERS: usize = 10;
+
+/// A file or folder and when it was last opened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentEntry {
+    pub path: PathBuf,
+    pub opened: SystemTime,
+}
+
+impl RecentEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or_else(|| self.path.display().to_string(), |n| {
+                n.to_string_lossy().to_string()
+            })
+    }
+
+    /// How long ago the entry was opened, like `3 hours ago`.
+    pub fn age(&self) -> String {
+        let secs = self.opened.elapsed().map_or(0, |d| d.as_secs());
+        let (amount, unit) = match secs {
+            0..60 => return String::from("just now"),
+            60..3_600 => (secs / 60, "minute"),
+            3_600..86_400 => (secs / 3_600, "hour"),
+            _ => (secs / 86_400, "day"),
+        };
+        let plural = if amount == 1 { "" } else { "s" };
+        format!("{amount} {unit}{plural} ago")
+    }
+}
+
+/// The files and folders opened lately, the newest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recent {
+    pub files: Vec<RecentEntry>,
+    pub folders: Vec<RecentEntry>,
+}
+
+impl Recent {
+    pub fn path() -> Option<PathBuf> {
+        Some(registers::paths::data_dir()?.join("recent.json"))
+    }
+
+    fn load() -> Self {
+        let Some(content) =
+            Self::path().and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(recent) => recent,
+            Err(e) => {
+                println!("Ignoring invalid recent files list: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    fn store(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(self)
+                    .map_err(std::io::Error::other)?;
+                std::fs::write(&path, json)
+            });
+        if let Err(e) = written {
+            println!("Unable to write recent files {:?}: {e}", path);
+        }
+    }
+}
+
+fn global() -> MutexGuard<'static, Recent> {
+    static RECENT: OnceLock<Mutex<Recent>> = OnceLock::new();
+    RECENT
+        .get_or_init(|| Mutex::new(Recent::load()))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+pub fn current() -> Recent {
+    global().clone()
+}
+
+fn push(entries: &mut Vec<RecentEntry>, path: &Path, max: usize) {
+    entries.retain(|e| e.path != path);
+    entries.insert(0, RecentEntry {
+        path: path.to_path_buf(),
+        opened: SystemTime::now(),
+    });
+    entries.truncate(max);
+}
+
+pub fn add_file(path: &Path) {
+    let mut recent = global();
+    push(&mut recent.files, path, MAX_FILES);
+    recent.store();
+}
+
+pub fn add_folder(path: &Path) {
+    let mut recent = global();
+    push(&mut recent.folders, path, MAX_FOLDERS);
+    recent.store();
+}
+
+/// Drops the files and folders that no longer exist in `fs`.
+pub fn prune(fs: &dyn FileSystem) {
+    let mut recent = global();
+    let before = (recent.files.len(), recent.folders.len());
+    recent.files.retain(|e| fs.is_file(&e.path));
+    recent.folders.retain(|e| fs.is_dir(&e.path));
+    if (recent.files.len(), recent.folders.len()) != before {
+        recent.store();
+    }
+}
+
+/// Forgets a file or a folder.
+pub fn remove(path: &Path) {
+    let mut recent = global();
+    recent.files.retain(|e| e.path != path);
+    recent.folders.retain(|e| e.path != path);
+    recent.store();
+}
+
+/// The folder opened last, where file pickers start.
+pub fn workspace() -> Option<PathBuf> {
+    global().folders.first().map(|e| e.path.clone())
+}
+
+/// Where a file picker should start: the last folder, or the folder of
+/// the last file.
+pub fn start_dir() -> Option<PathBuf> {
+    let recent = global();
+    recent
+        .folders
+        .first()
+        .map(|e| e.path.clone())
+        .or_else(|| recent.files.first()?.path.parent().map(Path::to_path_buf))
+}
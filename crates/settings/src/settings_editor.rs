@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use iced::{
+    alignment::Vertical,
+    futures::{stream, StreamExt},
+    widget::{
+        checkbox, column, container, row, scrollable, text, text_input, Column,
+    },
+    Color, Element, Length, Subscription, Task,
+};
+use registers::{filesystem::{FileSystem, LocalFs}, Dock, Event, Register};
+use toml::Value;
+use ui::labeled_button;
+
+use crate::{fonts, store, Settings};
+
+/// A searchable list of every setting. Changes are written back to the
+/// settings file, and edits made to the file are picked up while running.
+pub struct SettingsEditor {
+    visible: bool,
+    search: String,
+    /// Inputs not applied yet.
+    drafts: BTreeMap<String, String>,
+    /// Why the last input of a setting was rejected.
+    errors: BTreeMap<String, String>,
+    /// Why the settings file couldn't be reloaded.
+    file_error: Option<String>,
+}
+
+impl SettingsEditor {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            search: String::new(),
+            drafts: BTreeMap::new(),
+            errors: BTreeMap::new(),
+            file_error: None,
+        }
+    }
+
+    /// The settings as `section.key` leaves, in file order.
+    fn entries(settings: &Settings) -> Vec<(String, Value)> {
+        let mut entries = Vec::new();
+        if let Ok(Value::Table(table)) = Value::try_from(settings) {
+            flatten("", table, &mut entries);
+        }
+        entries
+    }
+
+    fn apply(&mut self, key: String, input: String) -> Task<Event> {
+        let old = store::current();
+        let Ok(mut value) = Value::try_from(&*old) else {
+            return Task::none();
+        };
+        let Some(slot) = key.split('.').try_fold(&mut value, |v, part| {
+            v.get_mut(part)
+        }) else {
+            return Task::none();
+        };
+
+        let parsed = match parse_like(slot, &input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.errors.insert(key, e);
+                return Task::none();
+            }
+        };
+        *slot = parsed.clone();
+
+        match value.try_into::<Settings>() {
+            Ok(new) => {
+                self.drafts.remove(&key);
+                self.errors.remove(&key);
+                store::set(&key, &parsed);
+                self.changed(&old, new)
+            }
+            Err(e) => {
+                self.errors.insert(key, e.to_string());
+                Task::none()
+            }
+        }
+    }
+
+    /// Makes `new` the settings in use and tells the other registers.
+    fn changed(&mut self, old: &Settings, new: Settings) -> Task<Event> {
+        let theme = (new.editor.theme != old.editor.theme)
+            .then(|| new.editor.syntax_theme());
+        let font = fonts::load_file(&new.editor.font_file);
+        store::replace(new);
+        let changed = font.chain(Task::done(Event::SettingsChanged));
+        match theme {
+            Some(theme) => {
+                changed.chain(Task::done(Event::ThemeChanged(theme)))
+            }
+            None => changed,
+        }
+    }
+
+    fn reload(&mut self) -> Task<Event> {
+        match store::read() {
+            Ok(new) => {
+                self.file_error = None;
+                let old = store::current();
+                if *old == new {
+                    Task::none()
+                } else {
+                    self.changed(&old, new)
+                }
+            }
+            Err(e) => {
+                self.file_error = Some(e);
+                Task::none()
+            }
+        }
+    }
+}
+
+impl Register for SettingsEditor {
+    fn update(&mut self, event: Event) -> Task<Event> {
+        match event {
+            Event::ToggleSettings => {
+                self.visible = !self.visible;
+                Task::none()
+            }
+            Event::SettingsSearch(search) => {
+                self.search = search;
+                Task::none()
+            }
+            Event::SettingsInput(key, input) => {
+                self.errors.remove(&key);
+                self.drafts.insert(key, input);
+                Task::none()
+            }
+            Event::SettingsApply(key, input) => self.apply(key, input),
+            Event::SettingsFileChanged => self.reload(),
+            Event::UiThemeChanged(theme) => {
+                self.apply(String::from("ui.theme"), theme.to_string())
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Event> {
+        let Some(path) = store::path() else {
+            return Subscription::none();
+        };
+        // Watched once the subscription starts, not each time it is built
+        let changes = stream::once(async move { LocalFs.watch(&path) })
+            .flatten()
+            .map(|_| Event::SettingsFileChanged);
+        Subscription::run_with_id("settings file", changes)
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        if !self.visible {
+            return Column::new().into();
+        }
+        let settings = store::current();
+
+        let error_color = Color::from_rgb(0.9, 0.4, 0.4);
+        let search = self.search.to_lowercase();
+        let entries = Self::entries(&settings)
+            .into_iter()
+            .filter(|(key, _)| key.to_lowercase().contains(&search))
+            .fold(Column::new().spacing(4), |entries, (key, value)| {
+                let control: Element<'_, Event> = match value {
+                    Value::Boolean(checked) => {
+                        let toggled = key.clone();
+                        checkbox("", checked)
+                            .on_toggle(move |b| {
+                                Event::SettingsApply(
+                                    toggled.clone(),
+                                    b.to_string(),
+                                )
+                            })
+                            .into()
+                    }
+                    value => {
+                        let input = self
+                            .drafts
+                            .get(&key)
+                            .cloned()
+                            .unwrap_or_else(|| display(&value));
+                        let edited = key.clone();
+                        text_input("", &input)
+                            .on_input(move |s| {
+                                Event::SettingsInput(edited.clone(), s)
+                            })
+                            .on_submit(Event::SettingsApply(key.clone(), input))
+                            .width(Length::FillPortion(2))
+                            .into()
+                    }
+                };
+                let error = self.errors.get(&key).map(|e| {
+                    text(e.clone()).size(12).color(error_color)
+                });
+                entries.push(
+                    column![
+                        row![text(key).width(Length::FillPortion(1)), control]
+                            .spacing(8)
+                            .align_y(Vertical::Center),
+                    ]
+                    .push_maybe(error),
+                )
+            });
+
+        let header = row![
+            text("Settings"),
+            text_input("Search settings", &self.search)
+                .on_input(Event::SettingsSearch),
+            labeled_button("Close", Event::ToggleSettings),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let file_error = self.file_error.as_ref().map(|e| {
+            text(format!("The settings file is invalid: {e}"))
+                .color(error_color)
+        });
+
+        container(
+            column![header]
+                .push_maybe(file_error)
+                .push(scrollable(entries).height(Length::Fixed(300.0)))
+                .spacing(settings.ui.spacing),
+        )
+        .style(container::bordered_box)
+        .padding(settings.ui.padding)
+        .width(Length::Fill)
+        .into()
+    }
+
+    fn title(&self) -> String {
+        String::from("Settings")
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Right
+    }
+
+    fn is_open(&self) -> bool {
+        self.visible
+    }
+}
+
+fn flatten(
+    prefix: &str,
+    table: toml::Table,
+    entries: &mut Vec<(String, Value)>,
+) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Table(table) => flatten(&key, table, entries),
+            value => entries.push((key, value)),
+        }
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Parses an input as a value of the same type as `old`.
+fn parse_like(old: &Value, input: &str) -> Result<Value, String> {
+    let input = input.trim();
+    match old {
+        Value::String(_) => Ok(Value::String(input.to_string())),
+        Value::Integer(_) => input
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| String::from("Expected a whole number")),
+        Value::Float(_) => input
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| String::from("Expected a number")),
+        Value::Boolean(_) => input
+            .parse()
+            .map(Value::Boolean)
+            .map_err(|_| String::from("Expected true or false")),
+        _ => Err(String::from("Edit this value in the settings file")),
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use toml_edit::{DocumentMut, Item};
+
+use crate::Settings;
+
+fn global() -> &'static Mutex<Arc<Settings>> {
+    static SETTINGS: OnceLock<Mutex<Arc<Settings>>> = OnceLock::new();
+    SETTINGS.get_or_init(|| Mutex::new(Arc::new(load())))
+}
+
+/// The settings in use, read from the settings file on first access.
+pub fn current() -> Arc<Settings> {
+    global()
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+        .clone()
+}
+
+pub fn replace(settings: Settings) {
+    *global().lock().expect("Let's hope the lock isn't poisoned") =
+        Arc::new(settings);
+}
+
+pub fn path() -> Option<PathBuf> {
+    Some(registers::paths::config_dir()?.join("settings.toml"))
+}
+
+/// Reads the settings file, falling back to the defaults when missing.
+pub fn load() -> Settings {
+    let Some(path) = path() else {
+        return Settings::default();
+    };
+    if !path.exists() {
+        return Settings::default();
+    }
+    read().unwrap_or_else(|e| {
+        println!("Ignoring invalid settings file {:?}: {e}", path);
+        Settings::default()
+    })
+}
+
+/// Parses the settings file, reporting why it is invalid.
+pub fn read() -> Result<Settings, String> {
+    let path = path().ok_or("No configuration directory")?;
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    toml::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Writes a single `section.key` into the settings file, leaving the
+/// other keys, their comments and their order as the user wrote them.
+pub fn set(key: &str, value: &toml::Value) {
+    let Some(path) = path() else {
+        return;
+    };
+    let written = edit(&path, key, value).and_then(|content| {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, content))
+    });
+    if let Err(e) = written {
+        println!("Unable to write settings {:?}: {e}", path);
+    }
+}
+
+fn edit(
+    path: &Path,
+    key: &str,
+    value: &toml::Value,
+) -> std::io::Result<String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut document = content
+        .parse::<DocumentMut>()
+        .map_err(std::io::Error::other)?;
+    let mut value = value
+        .to_string()
+        .parse::<toml_edit::Value>()
+        .map_err(std::io::Error::other)?;
+
+    let mut parts = key.split('.').collect::<Vec<_>>();
+    let leaf = parts.pop().unwrap_or(key);
+    let mut table = document.as_table_mut();
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .ok_or_else(|| {
+                std::io::Error::other(format!("{part} is not a table"))
+            })?;
+    }
+    if let Some(old) = table.get(leaf).and_then(Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table.insert(leaf, Item::Value(value));
+    Ok(document.to_string())
+}
@@ -0,0 +1,73 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, container, horizontal_space, text, Row},
+    Element, Length, Task,
+};
+use registers::{Dock, Event, Register, StatusItem, StatusSide};
+
+/// Text size of the status bar items.
+const TEXT_SIZE: f32 = 13.0;
+
+/// A bar at the bottom of the window made of the items other registers
+/// send through [`Event::SetStatusItems`], left items first.
+pub struct StatusBar {
+    /// The items of every register, in the order they were first sent.
+    items: Vec<(String, Vec<StatusItem>)>,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    fn side(&self, side: StatusSide) -> impl Iterator<Item = &StatusItem> {
+        self.items
+            .iter()
+            .flat_map(|(_, items)| items)
+            .filter(move |item| item.side == side)
+    }
+}
+
+impl Register for StatusBar {
+    fn update(&mut self, _event: Event) -> Task<Event> {
+        if let Event::SetStatusItems(source, items) = _event {
+            match self.items.iter_mut().find(|(s, _)| *s == source) {
+                Some((_, current)) => *current = items,
+                None => self.items.push((source, items)),
+            }
+        }
+        Task::none()
+    }
+
+    fn view(&self) -> Element<'_, Event> {
+        let left = self.side(StatusSide::Left).fold(Row::new(), |row, item| {
+            row.push(item_view(item))
+        });
+        let right = self.side(StatusSide::Right).fold(Row::new(), |row, item| {
+            row.push(item_view(item))
+        });
+        Row::new()
+            .push(left.spacing(2))
+            .push(horizontal_space())
+            .push(right.spacing(2))
+            .align_y(Vertical::Center)
+            .width(Length::Fill)
+            .into()
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Footer
+    }
+}
+
+fn item_view(item: &StatusItem) -> Element<'_, Event> {
+    let label = text(item.text.as_str()).size(TEXT_SIZE);
+    match &item.command {
+        Some(command) => button(label)
+            .padding([2, 6])
+            .style(button::text)
+            .on_press(command.as_ref().clone())
+            .into(),
+        None => container(label).padding([2, 6]).into(),
+    }
+}
@@ -0,0 +1,193 @@
+//! Drives registers without a window, for tests.
+//!
+//! A [`Harness`] sends events to a register, a whole [`ZenCore`] or a
+//! single one, and runs the tasks they return on the spot, sending back
+//! what they produce until nothing is left. Window, clipboard and widget
+//! operations are dropped and subscriptions never run, so timers like
+//! auto-save only tick when their event is sent.
+//!
+//! File dialogs are answered by a [`ScriptedPicker`] handed to the editor,
+//! and buffers can be read from and written to a [`MemoryFs`]. Before
+//! building Zen, the harness points the configuration and data directories
+//! to a scratch folder, see [`scratch`], so the settings, dock layout and
+//! other files tests read and write aren't the user's.
+
+pub mod picker;
+pub use editor::filesystem::MemoryFs;
+pub use picker::ScriptedPicker;
+
+use std::{any::Any, collections::VecDeque, path::PathBuf, sync::Arc};
+
+use editor::{editor_core::EditorCore, session::SessionNode};
+use iced::{
+    futures::{executor, StreamExt},
+    Task, Theme,
+};
+use iced_runtime::{task, Action};
+use zen_core::{Editor, Event, FileSystem, Register, ZenCore};
+
+/// Events one event may lead to before it is taken for a loop.
+const MAX_EVENTS: usize = 10_000;
+
+/// Points the configuration and data directories to a folder of the
+/// temporary directory, the same for every test of this process. Call it
+/// before building a register, as most load their files when built.
+pub fn scratch() -> PathBuf {
+    let scratch = std::env::temp_dir()
+        .join(format!("zen-tests-{}", std::process::id()));
+    zen_core::paths::redirect(&scratch);
+    scratch
+}
+
+/// The events `task` produces, once it ran to completion.
+pub fn resolve(task: Task<Event>) -> Vec<Event> {
+    let Some(mut stream) = task::into_stream(task) else {
+        return Vec::new();
+    };
+    executor::block_on(async {
+        let mut events = Vec::new();
+        // Other actions are dropped as they come, ending the tasks waiting
+        // for an answer to them
+        while let Some(action) = stream.next().await {
+            if let Action::Output(event) = action {
+                events.push(event);
+            }
+        }
+        events
+    })
+}
+
+pub struct Harness<R> {
+    register: R,
+    /// Every event the register was given, in order.
+    pub events: Vec<Event>,
+}
+
+impl<R: Register + 'static> Harness<R> {
+    pub fn new(register: R) -> Self {
+        Self {
+            register,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn register(&self) -> &R {
+        &self.register
+    }
+
+    pub fn register_mut(&mut self) -> &mut R {
+        &mut self.register
+    }
+
+    /// Sends `event`, then what its tasks produce until nothing is left.
+    pub fn send(&mut self, event: Event) -> &mut Self {
+        self.settle(VecDeque::from([event]))
+    }
+
+    /// Runs `task`, like the one Zen starts with, sending what it produces.
+    pub fn run(&mut self, task: Task<Event>) -> &mut Self {
+        self.settle(resolve(task).into())
+    }
+
+    fn settle(&mut self, mut queue: VecDeque<Event>) -> &mut Self {
+        let mut sent = 0;
+        while let Some(event) = queue.pop_front() {
+            if matches!(event, Event::None) {
+                continue;
+            }
+            sent += 1;
+            assert!(
+                sent <= MAX_EVENTS,
+                "Over {MAX_EVENTS} events sent, the last one being {event:?}"
+            );
+            self.events.push(event.clone());
+            queue.extend(resolve(self.register.update(event)));
+        }
+        self
+    }
+
+    /// The register if it is an editor, or the editor of a `ZenCore`.
+    pub fn editor(&self) -> &Editor {
+        let register = &self.register as &dyn Any;
+        register
+            .downcast_ref::<Editor>()
+            .or_else(|| register.downcast_ref::<ZenCore>()?.get())
+            .expect("The register holds no editor")
+    }
+
+    /// The pane editing what the focused one shows.
+    pub fn focused(&self) -> &EditorCore {
+        self.editor().focused_core().expect("No pane is focused")
+    }
+
+    /// The files open in the focused pane, in tab order.
+    pub fn open_files(&self) -> Vec<PathBuf> {
+        self.focused().files.iter().map(|f| f.path.clone()).collect()
+    }
+
+    /// The tab shown by the focused pane.
+    pub fn active_tab(&self) -> usize {
+        self.focused().active_file
+    }
+
+    pub fn active_path(&self) -> Option<PathBuf> {
+        self.focused().active_path().cloned()
+    }
+
+    /// The text of the focused buffer.
+    pub fn text(&self) -> String {
+        self.focused().get_content()
+    }
+
+    /// The split tree of the panes and the tabs of each one.
+    pub fn layout(&self) -> SessionNode {
+        self.editor().session().layout
+    }
+
+    pub fn pane_count(&self) -> usize {
+        self.editor().panes().iter().count()
+    }
+}
+
+impl Harness<ZenCore> {
+    /// Zen with only an editor, asking `picker` for files.
+    pub fn with_editor(picker: &ScriptedPicker) -> Self {
+        scratch();
+        let mut editor = Editor::new();
+        editor.set_picker(Arc::new(picker.clone()));
+        Self::new(ZenCore::new_with(
+            String::from("Zen"),
+            Theme::Dark,
+            vec![Box::new(editor)],
+        ))
+    }
+
+    /// Zen with every register it runs with, in the same order, asking
+    /// `picker` for files and reading and writing them in `fs`.
+    pub fn app(picker: &ScriptedPicker, fs: &MemoryFs) -> Self {
+        scratch();
+        let fs: Arc<dyn FileSystem> = Arc::new(fs.clone());
+        let mut editor = Editor::new();
+        editor.set_picker(Arc::new(picker.clone()));
+        editor.set_filesystem(fs.clone());
+        Self::new(ZenCore::new_with(
+            String::from("Zen"),
+            Theme::Dark,
+            zen_core::default_registers(editor, fs),
+        ))
+    }
+
+    /// Zen with only an editor, asking `picker` for files and reading and
+    /// writing them in `fs`.
+    pub fn in_memory(picker: &ScriptedPicker, fs: &MemoryFs) -> Self {
+        scratch();
+        let mut editor = Editor::new();
+        editor.set_picker(Arc::new(picker.clone()));
+        editor.set_filesystem(Arc::new(fs.clone()));
+        Self::new(ZenCore::new_with(
+            String::from("Zen"),
+            Theme::Dark,
+            vec![Box::new(editor)],
+        ))
+    }
+}
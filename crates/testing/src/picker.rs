@@ -0,0 +1,65 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use editor::picker::FilePicker;
+use iced::futures::{
+    future::{self, BoxFuture},
+    FutureExt,
+};
+
+/// Answers the file dialogs with the paths it was given, in order, then as
+/// if they were cancelled. Clones share their answers, so more can be given
+/// once the picker was handed to the editor.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedPicker {
+    answers: Arc<Mutex<VecDeque<Option<PathBuf>>>>,
+}
+
+impl ScriptedPicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers the next dialog with `path`.
+    pub fn answer(&self, path: impl Into<PathBuf>) -> &Self {
+        self.push(Some(path.into()))
+    }
+
+    /// Cancels the next dialog.
+    pub fn cancel(&self) -> &Self {
+        self.push(None)
+    }
+
+    fn push(&self, answer: Option<PathBuf>) -> &Self {
+        self.answers
+            .lock()
+            .expect("Let's hope the lock isn't poisoned")
+            .push_back(answer);
+        self
+    }
+
+    fn next(&self) -> Option<PathBuf> {
+        self.answers
+            .lock()
+            .expect("Let's hope the lock isn't poisoned")
+            .pop_front()
+            .flatten()
+    }
+}
+
+impl FilePicker for ScriptedPicker {
+    fn pick_file(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        future::ready(self.next()).boxed()
+    }
+
+    fn pick_folder(&self) -> BoxFuture<'static, Option<PathBuf>> {
+        future::ready(self.next()).boxed()
+    }
+
+    fn save_file(&self, _name: &str) -> BoxFuture<'static, Option<PathBuf>> {
+        future::ready(self.next()).boxed()
+    }
+}
@@ -0,0 +1,259 @@
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use iced::{widget::{pane_grid, text_editor}, Theme};
+use testing::{Harness, MemoryFs, ScriptedPicker};
+use zen_core::{AutoSave, Editor, Event, Marker, MarkerKind, Target, ZenCore};
+
+/// What `fs` holds at `path`, without the line end the editor adds.
+fn written(fs: &MemoryFs, path: &str) -> Option<String> {
+    let bytes = fs.contents(Path::new(path))?;
+    Some(String::from_utf8(bytes).ok()?.trim_end().to_string())
+}
+
+fn move_to(motion: text_editor::Motion) -> Event {
+    Event::EditorAction(text_editor::Action::Move(motion))
+}
+
+fn insert(c: char) -> Event {
+    Event::EditorAction(text_editor::Action::Edit(text_editor::Edit::Insert(c)))
+}
+
+/// Zen with every register, opening `paths` from `fs` in order.
+fn opened(fs: &MemoryFs, paths: &[&str]) -> Harness<ZenCore> {
+    let picker = ScriptedPicker::new();
+    let mut zen = Harness::app(&picker, fs);
+    for path in paths {
+        picker.answer(*path);
+        zen.send(Event::OpenFile);
+    }
+    zen
+}
+
+#[test]
+fn open_edit_and_save() {
+    let fs = MemoryFs::new().with("/work/a.txt", "hello");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    assert_eq!(zen.open_files(), vec![PathBuf::from("/work/a.txt")]);
+    assert_eq!(zen.text().trim_end(), "hello");
+
+    zen.send(move_to(text_editor::Motion::DocumentEnd))
+        .send(Event::InsertText(String::from("!")));
+    assert!(zen.focused().files[0].dirty);
+    assert_eq!(written(&fs, "/work/a.txt").as_deref(), Some("hello"));
+
+    zen.send(Event::Save);
+    assert_eq!(written(&fs, "/work/a.txt").as_deref(), Some("hello!"));
+    assert!(!zen.focused().files[0].dirty);
+}
+
+#[test]
+fn every_register_lets_the_editor_open_files() {
+    let picker = ScriptedPicker::new();
+    let fs = MemoryFs::new().with("/work/a.txt", "hello");
+    let mut zen = Harness::app(&picker, &fs);
+    picker.answer("/work/a.txt");
+    zen.send(Event::OpenFile);
+    assert_eq!(zen.open_files(), vec![PathBuf::from("/work/a.txt")]);
+}
+
+#[test]
+fn cancelled_open_opens_nothing() {
+    let picker = ScriptedPicker::new();
+    let mut zen = Harness::in_memory(&picker, &MemoryFs::new());
+    picker.cancel();
+    zen.send(Event::OpenFile);
+    assert!(zen.open_files().is_empty());
+}
+
+#[test]
+fn save_as_names_an_untitled_buffer() {
+    let picker = ScriptedPicker::new();
+    let fs = MemoryFs::new();
+    let mut zen = Harness::in_memory(&picker, &fs);
+    zen.send(Event::NewFile)
+        .send(Event::InsertText(String::from("draft")));
+    assert!(zen.focused().files[0].untitled);
+
+    picker.answer("/work/draft.txt");
+    zen.send(Event::Save);
+    assert_eq!(written(&fs, "/work/draft.txt").as_deref(), Some("draft"));
+    assert_eq!(zen.open_files(), vec![PathBuf::from("/work/draft.txt")]);
+    assert!(!zen.focused().files[0].untitled);
+    assert!(!zen.focused().files[0].dirty);
+}
+
+#[test]
+fn saving_a_read_only_file_reports_it() {
+    let fs = MemoryFs::new()
+        .with("/work/locked.txt", "locked")
+        .with_read_only("/work/locked.txt");
+    let mut zen = opened(&fs, &["/work/locked.txt"]);
+    zen.send(Event::InsertText(String::from("edit ")))
+        .send(Event::Save);
+
+    assert!(zen.events.iter().any(|e| matches!(e, Event::FileError(..))));
+    assert!(zen.focused().file_error().is_some());
+    assert!(zen.focused().files[0].dirty);
+    assert_eq!(written(&fs, "/work/locked.txt").as_deref(), Some("locked"));
+
+    zen.send(Event::ClearFileError);
+    assert!(zen.focused().file_error().is_none());
+}
+
+#[test]
+fn split_and_close_panes() {
+    let fs = MemoryFs::new().with("/work/a.txt", "a");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    assert_eq!(zen.pane_count(), 1);
+
+    zen.send(Event::Split(pane_grid::Axis::Vertical, 0));
+    assert_eq!(zen.pane_count(), 2);
+    assert!(matches!(
+        zen.layout(),
+        editor::session::SessionNode::Split { vertical: true, .. }
+    ));
+    // The new pane is focused, and empty
+    assert!(zen.open_files().is_empty());
+
+    zen.send(Event::Close(1));
+    assert_eq!(zen.pane_count(), 1);
+    assert_eq!(zen.open_files(), vec![PathBuf::from("/work/a.txt")]);
+}
+
+#[test]
+fn addressed_save_writes_a_tab_that_isnt_shown() {
+    let fs = MemoryFs::new()
+        .with("/work/a.txt", "hello")
+        .with("/work/b.txt", "bee");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    zen.send(move_to(text_editor::Motion::DocumentEnd));
+    zen.send(Event::OpenPath(PathBuf::from("/work/b.txt")));
+    zen.send(move_to(text_editor::Motion::Right));
+    assert_eq!(zen.active_path(), Some(PathBuf::from("/work/b.txt")));
+
+    let a = Target::file("/work/a.txt");
+    zen.send(a.clone().send(Event::InsertText(String::from("!"))))
+        .send(a.send(Event::Save));
+
+    // Inserted where the caret was left in a.txt
+    assert_eq!(written(&fs, "/work/a.txt").as_deref(), Some("hello!"));
+    assert_eq!(written(&fs, "/work/b.txt").as_deref(), Some("bee"));
+    assert_eq!(zen.active_path(), Some(PathBuf::from("/work/b.txt")));
+    assert_eq!(zen.text().trim_end(), "bee");
+    assert_eq!(zen.focused().cursor_position(), (0, 1));
+}
+
+#[test]
+fn recorded_macro_plays_the_times_asked() {
+    let fs = MemoryFs::new().with("/work/a.txt", "");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    zen.send(Event::RecordMacro(Some(String::from("ab"))))
+        .send(insert('a'))
+        .send(insert('b'))
+        .send(Event::RecordMacro(None));
+    assert_eq!(zen.text().trim_end(), "ab");
+
+    zen.send(Event::PlayMacro(Some(String::from("ab")), Some(3)));
+    assert_eq!(zen.text().trim_end(), "abababab");
+}
+
+#[test]
+fn macro_played_to_the_end_runs_in_chunks() {
+    let lines = ["x"; 250].join("\n");
+    let fs = MemoryFs::new().with("/work/lines.txt", lines);
+    let mut zen = opened(&fs, &["/work/lines.txt"]);
+    zen.send(move_to(text_editor::Motion::DocumentStart))
+        .send(Event::RecordMacro(Some(String::from("dash"))))
+        .send(insert('-'))
+        .send(move_to(text_editor::Motion::Down))
+        .send(move_to(text_editor::Motion::Home))
+        .send(Event::RecordMacro(None))
+        .send(Event::PlayMacro(Some(String::from("dash")), None));
+
+    let text = zen.text();
+    assert_eq!(text.lines().count(), 250);
+    assert!(text.lines().all(|line| line == "-x"), "{text}");
+    // 249 runs, played 100 at a time
+    let resumed = zen.events.iter()
+        .filter(|e| matches!(e, Event::ResumeMacro))
+        .count();
+    assert_eq!(resumed, 2);
+}
+
+#[test]
+fn auto_save_of_an_older_version_keeps_the_buffer_dirty() {
+    let fs = MemoryFs::new().with("/work/a.txt", "");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    let path = PathBuf::from("/work/a.txt");
+
+    zen.send(Event::InsertText(String::from("1")));
+    let first = zen.focused().files[0].digest();
+    // Edited again while auto-save wrote the first version
+    zen.send(Event::InsertText(String::from("2")));
+    zen.send(Event::AutoSaved(vec![(path.clone(), first)]));
+    assert!(zen.focused().files[0].dirty);
+
+    let second = zen.focused().files[0].digest();
+    zen.send(Event::AutoSaved(vec![(path, second)]));
+    assert!(!zen.focused().files[0].dirty);
+}
+
+#[test]
+fn save_of_an_older_version_keeps_the_buffer_dirty() {
+    let fs = MemoryFs::new().with("/work/a.txt", "");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    let path = PathBuf::from("/work/a.txt");
+
+    zen.send(Event::InsertText(String::from("1")));
+    let first = zen.focused().files[0].digest();
+    // Edited again while the first version was written
+    zen.send(Event::InsertText(String::from("2")));
+    zen.send(Event::Saved(Some((path.clone(), path.clone(), first))));
+    assert!(zen.focused().files[0].dirty);
+
+    zen.send(Event::Save);
+    assert_eq!(written(&fs, "/work/a.txt").as_deref(), Some("12"));
+    assert!(!zen.focused().files[0].dirty);
+}
+
+#[test]
+fn switching_tabs_auto_saves_the_one_left() {
+    testing::scratch();
+    let fs = MemoryFs::new()
+        .with("/work/a.txt", "a")
+        .with("/work/b.txt", "b");
+    let mut editor = Editor::new();
+    editor.set_filesystem(Arc::new(fs.clone()));
+    editor.set_auto_save(AutoSave::off().on_switch());
+    let mut zen = Harness::new(ZenCore::new_with(
+        String::from("Zen"),
+        Theme::Dark,
+        vec![Box::new(editor)],
+    ));
+    zen.send(Event::OpenPath(PathBuf::from("/work/a.txt")))
+        .send(Event::OpenPath(PathBuf::from("/work/b.txt")))
+        .send(Event::InsertText(String::from("!")))
+        .send(Event::TabSelected(0));
+
+    assert_eq!(zen.active_path(), Some(PathBuf::from("/work/a.txt")));
+    assert_eq!(written(&fs, "/work/b.txt").as_deref(), Some("!b"));
+    assert!(!zen.focused().files[1].dirty);
+}
+
+#[test]
+fn lines_changed_since_the_last_commit_are_marked() {
+    let fs = MemoryFs::new().with("/work/a.txt", "one\ntwo\nthree");
+    let mut zen = opened(&fs, &["/work/a.txt"]);
+    let head = String::from("one\n2\nthree\nfour\n");
+    zen.send(Event::GitHead(PathBuf::from("/work/a.txt"), Some(head)));
+    assert_eq!(zen.focused().files[0].markers, vec![
+        Marker::new(1, MarkerKind::GitModified),
+        Marker::new(2, MarkerKind::GitRemoved),
+    ]);
+
+    zen.send(move_to(text_editor::Motion::DocumentEnd))
+        .send(Event::InsertText(String::from("\nfour")));
+    assert_eq!(zen.focused().files[0].markers, vec![
+        Marker::new(1, MarkerKind::GitModified),
+    ]);
+}
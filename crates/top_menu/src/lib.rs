@@ -1,17 +1,135 @@
-use iced::{alignment::Vertical, highlighter, widget::{horizontal_space, pick_list, row}, Element, Length, Task};
-use registers::{Event, Register};
-use ui::{button_with_icon, icon_code, Icon};
+use std::path::PathBuf;
+
+use iced::{
+    alignment::Vertical,
+    futures::{stream, StreamExt},
+    widget::{button, horizontal_space, pick_list, row, text},
+    Element, Length, Subscription, Task, Theme,
+};
+use iced_aw::menu::{Item, Menu, MenuBar};
+use registers::{
+    commands, filesystem::{FileSystem, LocalFs}, Command, CommandContext, Dock,
+    Event, MenuKind, Register, SyntaxTheme,
+};
+use settings::recent::{self, RecentEntry};
+use ui::{labeled_button, themes};
+
+/// Entries listed in the Recent Files and Recent Folders submenus.
+const RECENT_ENTRIES: usize = 10;
+/// Width of the dropdown menus.
+const MENU_WIDTH: f32 = 280.0;
 
 pub struct TopMenu {
-    pub theme: highlighter::Theme,
+    pub theme: SyntaxTheme,
+    pub ui_theme: Theme,
+    syntax_themes: Vec<SyntaxTheme>,
+    ui_themes: Vec<Theme>,
+    context: CommandContext,
 }
 
 impl TopMenu {
     pub fn new() -> Self {
+        let settings = settings::current();
         Self {
-            theme: highlighter::Theme::Base16Ocean,
+            theme: settings.editor.syntax_theme(),
+            ui_theme: settings.ui_theme().unwrap_or(Theme::Dark),
+            syntax_themes: themes::syntax_themes(),
+            ui_themes: themes::ui_themes(),
+            context: CommandContext::default(),
         }
     }
+
+    /// The dropdown of a menu, `None` when no command was registered in it.
+    fn menu(&self, kind: MenuKind) -> Option<Item<'_, Event, Theme, iced::Renderer>> {
+        let mut items: Vec<_> = commands::in_menu(kind)
+            .into_iter()
+            .map(|command| Item::new(self.entry(command)))
+            .collect();
+        if kind == MenuKind::File {
+            // After New File, Open File and Open Folder
+            let recent = recent::current();
+            let at = items.len().min(3);
+            items.insert(
+                at,
+                submenu("Recent Folders", &recent.folders, Event::FolderOpened),
+            );
+            items.insert(
+                at,
+                submenu("Recent Files", &recent.files, Event::OpenPath),
+            );
+        }
+        if items.is_empty() {
+            return None;
+        }
+        Some(Item::with_menu(
+            button(text(kind.to_string()))
+                .padding([4, 8])
+                .style(button::text)
+                .on_press(Event::None),
+            dropdown(items),
+        ))
+    }
+
+    fn entry(&self, command: Command) -> Element<'_, Event> {
+        let shortcut = command.shortcut().unwrap_or_default();
+        let enabled = self.context.allows(command.requires);
+        menu_button(
+            text(command.label),
+            text(shortcut).size(12),
+            enabled.then_some(command.event),
+        )
+    }
+}
+
+/// A submenu opening one of the recent `entries`.
+fn submenu<'a>(
+    label: &'a str,
+    entries: &[RecentEntry],
+    open: fn(PathBuf) -> Event,
+) -> Item<'a, Event, Theme, iced::Renderer> {
+    let items = if entries.is_empty() {
+        vec![Item::new(menu_button(text("Nothing Yet"), text(""), None))]
+    } else {
+        entries
+            .iter()
+            .take(RECENT_ENTRIES)
+            .map(|entry| {
+                Item::new(menu_button(
+                    text(entry.name()),
+                    text(entry.age()).size(12),
+                    Some(open(entry.path.clone())),
+                ))
+            })
+            .collect()
+    };
+    Item::with_menu(
+        menu_button(text(label), text("▸"), Some(Event::None)),
+        dropdown(items),
+    )
+}
+
+fn dropdown(
+    items: Vec<Item<'_, Event, Theme, iced::Renderer>>,
+) -> Menu<'_, Event, Theme, iced::Renderer> {
+    Menu::new(items).max_width(MENU_WIDTH).offset(5.0).spacing(2.0)
+}
+
+/// A menu entry, disabled without an event to send.
+fn menu_button<'a>(
+    label: impl Into<Element<'a, Event>>,
+    detail: impl Into<Element<'a, Event>>,
+    event: Option<Event>,
+) -> Element<'a, Event> {
+    button(
+        row![label.into(), horizontal_space(), detail.into()]
+            .spacing(10)
+            .align_y(Vertical::Center),
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .style(button::text)
+    .on_press_maybe(event)
+    .into()
 }
 
 impl Register for TopMenu {
@@ -21,16 +139,58 @@ impl Register for TopMenu {
                 self.theme = theme;
                 Task::none()
             }
+            Event::UiThemeChanged(theme) => {
+                self.ui_theme = theme;
+                Task::none()
+            }
+            Event::SettingsChanged => {
+                if let Some(theme) = settings::current().ui_theme() {
+                    self.ui_theme = theme;
+                }
+                Task::none()
+            }
+            Event::ThemesDirChanged => {
+                themes::reload();
+                self.syntax_themes = themes::syntax_themes();
+                self.ui_themes = themes::ui_themes();
+                Task::done(Event::ThemesReloaded)
+            }
+            Event::ContextChanged(context) => {
+                self.context = context;
+                Task::none()
+            }
             _ => Task::none(),
         }
     }
+
+    fn subscription(&self) -> Subscription<Event> {
+        let Some(dir) = themes::themes_dir() else {
+            return Subscription::none();
+        };
+        // Watched once it runs, as subscriptions are built after every update
+        let changes = stream::once(async move { LocalFs.watch(&dir) })
+            .flatten()
+            .map(|_| Event::ThemesDirChanged);
+        Subscription::run_with_id("themes directory", changes)
+    }
+
+    fn dock(&self) -> Dock {
+        Dock::Top
+    }
+
     fn view(&self) -> Element<'_, Event> {
+        let menus = MenuBar::new(
+            MenuKind::ALL.iter().filter_map(|kind| self.menu(*kind)).collect(),
+        )
+        .spacing(2.0);
         row![
-            button_with_icon(icon_code(Icon::File.into()), "Open", Event::OpenFile),
-            button_with_icon(icon_code(Icon::Save.into()), "Save", Event::Save),
-            button_with_icon(icon_code(Icon::Refresh.into()), "Reload", Event::ScanAllFiles),
+            menus,
             horizontal_space(),
-            pick_list(highlighter::Theme::ALL, Some(self.theme), Event::ThemeChanged),
+            labeled_button("Settings", Event::ToggleSettings),
+            text("UI"),
+            pick_list(self.ui_themes.as_slice(), Some(&self.ui_theme), Event::UiThemeChanged),
+            text("Syntax"),
+            pick_list(self.syntax_themes.as_slice(), Some(&self.theme), Event::ThemeChanged),
         ].spacing(5).align_y(Vertical::Center).padding(2).width(Length::Fill)
         .into()
     }
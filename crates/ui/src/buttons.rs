@@ -25,7 +25,7 @@ pub fn danger_button_with_icon(
     icon: impl Into<Element<'static, Event>>,
     label: &str,
     msg: Event,
-) -> tooltip::Tooltip<Event> {
+) -> tooltip::Tooltip<'_, Event> {
     tooltip(danger_button(
        icon.into(),
         msg,
@@ -37,7 +37,7 @@ pub fn button_with_icon(
     icon: impl Into<Element<'static, Event>>,
     label: &str,
     msg: Event,
-) -> tooltip::Tooltip<Event> {
+) -> tooltip::Tooltip<'_, Event> {
     tooltip(base_button(
        icon.into(),
         msg,
@@ -48,10 +48,10 @@ pub fn button_with_icon(
 pub fn labeled_button(
     label: &str,
     msg: Event,
-) -> button::Button<Event, iced::Theme, iced::Renderer> {
+) -> button::Button<'_, Event, iced::Theme, iced::Renderer> {
     base_button(text(label).align_y(alignment::Vertical::Center), msg)
 }
 
-pub fn debug_button_s(label: &str) -> button::Button<Event, iced::Theme, iced::Renderer> {
+pub fn debug_button_s(label: &str) -> button::Button<'_, Event, iced::Theme, iced::Renderer> {
     labeled_button(label, Event::None).width(Length::Shrink)
 }
\ No newline at end of file
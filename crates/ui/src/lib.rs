@@ -1,6 +1,7 @@
 pub mod icons;
 pub mod buttons;
 pub mod styles;
+pub mod themes;
 
 pub use icons::*;
 pub use buttons::*;
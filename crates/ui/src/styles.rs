@@ -1,4 +1,5 @@
 use iced::widget::{button, container, text_editor};
+use std::f32::consts::PI;
 use iced::{Border, Theme};
 
 pub fn tooltip_style(theme: &Theme) -> container::Style {
@@ -7,7 +8,7 @@ pub fn tooltip_style(theme: &Theme) -> container::Style {
         background: Some(palette.background.strong.color.into()),
         border: Border::default()
             .color(palette.background.strong.color)
-            .rounded(3.14)
+            .rounded(PI)
             .width(0.5),
         ..Default::default()
     }
@@ -19,7 +20,7 @@ pub fn tooltip_danger_style(theme: &Theme) -> container::Style {
         background: Some(palette.danger.strong.color.into()),
         border: Border::default()
             .color(palette.background.strong.color)
-            .rounded(3.14)
+            .rounded(PI)
             .width(1.0),
         ..Default::default()
     }
@@ -31,13 +32,13 @@ pub fn editor_style(theme: &Theme, _st: text_editor::Status) -> text_editor::Sty
     text_editor::Style {
         background: basic.background.into(),
         border: Border::default()
-            .rounded(3.14)
+            .rounded(PI)
             .color(palette.secondary.weak.color)
             .width(0.5),
-        icon: palette.primary.weak.color.into(),
-        placeholder: palette.secondary.weak.color.into(),
+        icon: palette.primary.weak.color,
+        placeholder: palette.secondary.weak.color,
         value: basic.text,
-        selection: palette.secondary.weak.color.into(),
+        selection: palette.secondary.weak.color,
     }
 }
 
@@ -47,7 +48,7 @@ pub fn button_danger_style(theme: &Theme) -> button::Style {
         background: Some(palette.danger.weak.color.into()),
         border: Border::default()
             .color(palette.danger.strong.color)
-            .rounded(2.0 * 3.14)
+            .rounded(2.0 * PI)
             .width(1.0),
         ..Default::default()
     };
@@ -60,7 +61,7 @@ pub fn button_styles(theme: &Theme, st: button::Status) -> button::Style {
         background: Some(palette.background.strong.color.into()),
         border: Border::default()
             .color(palette.background.strong.color)
-            .rounded(2.0 * 3.14)
+            .rounded(2.0 * PI)
             .width(1.0),
         ..Default::default()
     };
@@ -0,0 +1,261 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+};
+
+use iced::{highlighter, theme::Palette, Color, Theme};
+use registers::SyntaxTheme;
+use serde::Deserialize;
+use syntect::highlighting;
+
+/// Scopes coloured by each base16 slot, after the base16 styling guide.
+const BASE16_SCOPES: [(&str, usize); 10] = [
+    ("comment, punctuation.definition.comment", 0x03),
+    ("variable, support.variable", 0x08),
+    ("constant, constant.numeric, constant.language", 0x09),
+    ("entity.name.type, entity.name.class, support.type, support.class", 0x0A),
+    ("string, markup.inline.raw", 0x0B),
+    ("constant.character.escape, string.regexp", 0x0C),
+    ("entity.name.function, support.function, markup.heading", 0x0D),
+    ("keyword, storage, storage.type", 0x0E),
+    ("invalid, meta.deprecated", 0x0F),
+    ("keyword.operator, punctuation", 0x05),
+];
+
+/// A UI palette, as written in a `.json` file of the themes directory.
+#[derive(Deserialize)]
+struct PaletteFile {
+    name: Option<String>,
+    background: String,
+    text: String,
+    primary: String,
+    success: String,
+    danger: String,
+}
+
+/// The themes found in the themes directory.
+#[derive(Default)]
+struct Themes {
+    ui: Vec<Theme>,
+    syntax: BTreeMap<String, Arc<highlighting::Theme>>,
+    /// Bumped on every reload, so highlighters notice a changed theme
+    /// that kept its name.
+    generation: usize,
+}
+
+fn registry() -> MutexGuard<'static, Themes> {
+    static THEMES: OnceLock<Mutex<Themes>> = OnceLock::new();
+    THEMES
+        .get_or_init(|| Mutex::new(load(0)))
+        .lock()
+        .expect("Let's hope the lock isn't poisoned")
+}
+
+/// Holds `.json` UI palettes, `.tmTheme` and base16 `.yaml` syntax themes.
+pub fn themes_dir() -> Option<PathBuf> {
+    Some(registers::paths::config_dir()?.join("themes"))
+}
+
+/// Every built-in iced theme, followed by the custom palettes.
+pub fn ui_themes() -> Vec<Theme> {
+    let mut themes = Theme::ALL.to_vec();
+    themes.extend(registry().ui.iter().cloned());
+    themes
+}
+
+pub fn ui_theme(name: &str) -> Option<Theme> {
+    ui_themes()
+        .into_iter()
+        .find(|t| t.to_string().eq_ignore_ascii_case(name))
+}
+
+/// Every highlighter theme shipped with iced, followed by the custom ones.
+pub fn syntax_themes() -> Vec<SyntaxTheme> {
+    highlighter::Theme::ALL
+        .iter()
+        .map(|t| SyntaxTheme::BuiltIn(*t))
+        .chain(registry().syntax.keys().cloned().map(SyntaxTheme::Custom))
+        .collect()
+}
+
+/// The syntect theme behind a syntax theme. Unknown custom themes fall
+/// back to the default one.
+pub fn syntax_theme(theme: &SyntaxTheme) -> Arc<highlighting::Theme> {
+    match theme {
+        SyntaxTheme::BuiltIn(theme) => builtin(*theme),
+        SyntaxTheme::Custom(name) => registry()
+            .syntax
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| builtin(highlighter::Theme::Base16Ocean)),
+    }
+}
+
+pub fn is_dark(theme: &SyntaxTheme) -> bool {
+    match theme {
+        SyntaxTheme::BuiltIn(theme) => theme.is_dark(),
+        SyntaxTheme::Custom(_) => {
+            syntax_theme(theme).settings.background.map_or(true, |c| {
+                let luma = 0.299 * c.r as f32
+                    + 0.587 * c.g as f32
+                    + 0.114 * c.b as f32;
+                luma < 128.0
+            })
+        }
+    }
+}
+
+pub fn generation() -> usize {
+    registry().generation
+}
+
+/// Loads the themes again, after a file of the themes directory changed.
+pub fn reload() {
+    let generation = registry().generation;
+    let themes = load(generation + 1);
+    *registry() = themes;
+}
+
+fn builtin(theme: highlighter::Theme) -> Arc<highlighting::Theme> {
+    static DEFAULTS: OnceLock<BTreeMap<String, Arc<highlighting::Theme>>> =
+        OnceLock::new();
+    let key = match theme {
+        highlighter::Theme::SolarizedDark => "Solarized (dark)",
+        highlighter::Theme::Base16Mocha => "base16-mocha.dark",
+        highlighter::Theme::Base16Ocean => "base16-ocean.dark",
+        highlighter::Theme::Base16Eighties => "base16-eighties.dark",
+        highlighter::Theme::InspiredGitHub => "InspiredGitHub",
+    };
+    DEFAULTS.get_or_init(|| {
+        highlighting::ThemeSet::load_defaults()
+            .themes
+            .into_iter()
+            .map(|(name, theme)| (name, Arc::new(theme)))
+            .collect()
+    })[key]
+        .clone()
+}
+
+fn load(generation: usize) -> Themes {
+    let mut themes = Themes {
+        generation,
+        ..Themes::default()
+    };
+    let Some(entries) = themes_dir().and_then(|d| std::fs::read_dir(d).ok())
+    else {
+        return themes;
+    };
+    let mut paths: Vec<PathBuf> =
+        entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map_or(String::new(), |s| s.to_string_lossy().to_string());
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        let syntax = match extension.as_str() {
+            "json" => {
+                match load_palette(&path, &name) {
+                    Ok(theme) => themes.ui.push(theme),
+                    Err(e) => println!("Unable to load theme {:?}: {e}", path),
+                }
+                continue;
+            }
+            "tmtheme" => highlighting::ThemeSet::get_theme(&path)
+                .map_err(|e| e.to_string()),
+            "yaml" | "yml" => load_base16(&path),
+            _ => continue,
+        };
+        match syntax {
+            // Shared so highlighters made before a reload keep theirs
+            Ok(theme) => {
+                themes.syntax.insert(name, Arc::new(theme));
+            }
+            Err(e) => println!("Unable to load theme {:?}: {e}", path),
+        }
+    }
+    themes
+}
+
+fn load_palette(path: &Path, name: &str) -> Result<Theme, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: PaletteFile =
+        serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let color = |hex: &str| {
+        parse_hex(hex)
+            .map(|[r, g, b, a]| Color::from_rgba8(r, g, b, a as f32 / 255.0))
+            .ok_or(format!("Invalid color {hex}"))
+    };
+    let palette = Palette {
+        background: color(&file.background)?,
+        text: color(&file.text)?,
+        primary: color(&file.primary)?,
+        success: color(&file.success)?,
+        danger: color(&file.danger)?,
+    };
+    Ok(Theme::custom(file.name.unwrap_or(name.to_string()), palette))
+}
+
+/// Builds a syntect theme from a base16 scheme.
+fn load_base16(path: &Path) -> Result<highlighting::Theme, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let yaml: serde_yaml::Value =
+        serde_yaml::from_str(&content).map_err(|e| e.to_string())?;
+    // Newer schemes nest the colors under `palette`
+    let colors = yaml.get("palette").unwrap_or(&yaml);
+    let base = |slot: usize| {
+        let key = format!("base{slot:02X}");
+        colors
+            .get(key.as_str())
+            .and_then(|v| v.as_str())
+            .and_then(parse_hex)
+            .map(|[r, g, b, a]| highlighting::Color { r, g, b, a })
+            .ok_or(format!("Missing or invalid {key}"))
+    };
+
+    let scopes = BASE16_SCOPES
+        .iter()
+        .map(|(selector, slot)| {
+            Ok(highlighting::ThemeItem {
+                scope: selector.parse().map_err(|e| format!("{e:?}"))?,
+                style: highlighting::StyleModifier {
+                    foreground: Some(base(*slot)?),
+                    background: None,
+                    font_style: None,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let text = |key: &str| yaml.get(key)?.as_str().map(String::from);
+    Ok(highlighting::Theme {
+        name: text("scheme").or_else(|| text("name")),
+        author: text("author"),
+        settings: highlighting::ThemeSettings {
+            foreground: Some(base(0x05)?),
+            background: Some(base(0x00)?),
+            caret: Some(base(0x05)?),
+            line_highlight: Some(base(0x01)?),
+            selection: Some(base(0x02)?),
+            ..Default::default()
+        },
+        scopes,
+    })
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`, the `#` being optional.
+fn parse_hex(hex: &str) -> Option<[u8; 4]> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !hex.is_ascii() || !matches!(hex.len(), 6 | 8) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { byte(6)? } else { 255 };
+    Some([byte(0)?, byte(2)?, byte(4)?, alpha])
+}
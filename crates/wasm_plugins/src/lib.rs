@@ -0,0 +1,95 @@
+//! Plugins loaded at runtime from WebAssembly modules.
+//!
+//! Every directory of the [`plugins_dir`] holds a `plugin.toml`:
+//!
+//! ```toml
+//! name = "word-count"
+//! version = "0.1.0"
+//! api = "1.2"
+//! permissions = ["read-buffers", "status-items"]
+//! module = "plugin.wasm" # the default
+//! ```
+//!
+//! The permissions are only asked for: the plugin gets the ones the user
+//! also granted under `[plugins]` in the settings.
+//!
+//! Modules export their `memory`, `zen_alloc(len) -> ptr`, which the host
+//! uses to pass strings, and `zen_on_event(kind, a_ptr, a_len, b_ptr,
+//! b_len)`, called for each [`Lifecycle`](plugins::Lifecycle) event with
+//! `kind`, `a` and `b` being:
+//!
+//! | kind | event          | a            | b    |
+//! |------|----------------|--------------|------|
+//! | 0    | activated      |              |      |
+//! | 1    | file opened    | path         |      |
+//! | 2    | file saved     | path         |      |
+//! | 3    | file closed    | path         |      |
+//! | 4    | focus changed  | path or none |      |
+//! | 5    | buffer read    | path         | text |
+//! | 6    | command        | id           |      |
+//!
+//! Strings are UTF-8, an empty one has a null pointer. The module may free
+//! them once `zen_on_event` returns.
+//!
+//! The host functions are imported from the `zen` module and return `0`
+//! when done, `1` when the permission is missing and `2` for invalid
+//! arguments:
+//!
+//! - `log(text_ptr, text_len)`
+//! - `read_buffer(path_ptr, path_len)`
+//! - `insert_text(path_ptr, path_len, text_ptr, text_len)`
+//! - `replace_buffer(path_ptr, path_len, text_ptr, text_len)`
+//! - `register_command(menu, label_ptr, label_len, id_ptr, id_len)`, `menu`
+//!   being the index of File, Edit, Selection, View, Go or Help
+//! - `bind_key(char, modifiers, id_ptr, id_len)`, `modifiers` adding up
+//!   `1` for Ctrl, `2` for Shift and `4` for Alt
+//! - `clear_status()`
+//! - `add_status(right, text_ptr, text_len, id_ptr, id_len)`, the item runs
+//!   the command `id` when clicked, if not empty
+//! - `clipboard_entry(index, out_ptr)`, the newest copy being `0`
+//! - `push_clipboard(text_ptr, text_len)`
+//! - `read_register(char, out_ptr)`
+//! - `write_register(char, text_ptr, text_len)`, appending with an
+//!   uppercase `char`
+//!
+//! The ones reading text allocate it with `zen_alloc` and write its pointer
+//! and length at `out_ptr` as two little-endian `i32`, an empty string for
+//! a missing entry.
+//!
+//! Each call into a module runs on the UI thread, so it may only run a few
+//! milliseconds worth of instructions, and its memory is capped. A module
+//! that traps or runs out is stopped.
+
+pub mod manifest;
+pub mod runtime;
+pub use runtime::WasmPlugin;
+
+use std::path::PathBuf;
+
+/// Holds a directory for each plugin.
+pub fn plugins_dir() -> Option<PathBuf> {
+    Some(registers::paths::config_dir()?.join("plugins"))
+}
+
+/// Every plugin of the plugins directory, skipping the ones that fail to
+/// load.
+pub fn load_all() -> Vec<WasmPlugin> {
+    let Some(entries) = plugins_dir().and_then(|d| std::fs::read_dir(d).ok())
+    else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    dirs.iter()
+        .filter_map(|dir| {
+            manifest::read(dir)
+                .and_then(|(manifest, module)| WasmPlugin::load(manifest, &module))
+                .inspect_err(|e| println!("Unable to load plugin {:?}: {e}", dir))
+                .ok()
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+use plugins::{ApiVersion, Capability, Manifest};
+use serde::Deserialize;
+
+/// The `plugin.toml` of a plugin directory.
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    name: String,
+    version: String,
+    /// The plugin API the module was built against, like `1.0`.
+    api: String,
+    #[serde(default)]
+    permissions: Vec<String>,
+    /// The module, relative to the plugin directory.
+    #[serde(default = "default_module")]
+    module: PathBuf,
+}
+
+fn default_module() -> PathBuf {
+    PathBuf::from("plugin.wasm")
+}
+
+/// The manifest of the plugin in `dir` and the path of its module.
+pub fn read(dir: &Path) -> Result<(Manifest, PathBuf), String> {
+    let content = std::fs::read_to_string(dir.join("plugin.toml"))
+        .map_err(|e| e.to_string())?;
+    let file: ManifestFile = toml::from_str(&content).map_err(|e| e.to_string())?;
+    let api: ApiVersion = file.api.parse()?;
+    let capabilities = file.permissions
+        .iter()
+        .map(|p| p.parse::<Capability>())
+        .collect::<Result<Vec<_>, String>>()?;
+    let manifest = capabilities.into_iter().fold(
+        Manifest { api, ..Manifest::new(file.name, file.version) },
+        Manifest::with,
+    );
+    Ok((manifest, dir.join(file.module)))
+}
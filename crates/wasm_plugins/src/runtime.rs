@@ -0,0 +1,511 @@
+use std::path::Path;
+
+use plugins::{
+    Capability, Context, Lifecycle, Manifest, MenuKind, Modifiers, Plugin,
+    PluginError, StatusItem,
+};
+use wasmi::{
+    Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, TypedFunc,
+};
+
+/// Instructions a module may run for each event, a few milliseconds for
+/// wasmi, so a looping one can't hold up the editor.
+const FUEL_PER_CALL: u64 = 2_000_000;
+/// Largest memory of a module, in bytes.
+const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+/// Longest string the host reads from a module, in bytes.
+const MAX_STRING: usize = 16 * 1024 * 1024;
+
+const OK: i32 = 0;
+const DENIED: i32 = 1;
+const INVALID: i32 = 2;
+
+/// What the host functions reach during a call into a module.
+struct State {
+    name: String,
+    /// The plugin's context, only there while the module runs.
+    cx: Option<Context>,
+    /// The status items added since `clear_status`, sent once it returns.
+    status: Option<Vec<StatusItem>>,
+    limits: StoreLimits,
+}
+
+/// A plugin running in a WebAssembly module, see the [crate](crate)
+/// documentation for what it imports and exports. Its panel is never
+/// shown.
+pub struct WasmPlugin {
+    manifest: Manifest,
+    store: Store<State>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    on_event: TypedFunc<(i32, i32, i32, i32, i32), ()>,
+    /// Set once the module trapped, it isn't called anymore.
+    stopped: bool,
+}
+
+impl WasmPlugin {
+    pub fn load(manifest: Manifest, module: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(module).map_err(|e| e.to_string())?;
+        Self::from_bytes(manifest, &bytes)
+    }
+
+    /// Instantiates the module in `bytes`, in the binary format.
+    pub fn from_bytes(manifest: Manifest, bytes: &[u8]) -> Result<Self, String> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(|e| e.to_string())?;
+
+        let state = State {
+            name: manifest.name.clone(),
+            cx: None,
+            status: None,
+            limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+        let mut linker = Linker::new(&engine);
+        link(&mut linker)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or("The module exports no memory")?;
+        let alloc = instance
+            .get_typed_func(&store, "zen_alloc")
+            .map_err(|e| e.to_string())?;
+        let on_event = instance
+            .get_typed_func(&store, "zen_on_event")
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            manifest,
+            store,
+            memory,
+            alloc,
+            on_event,
+            stopped: false,
+        })
+    }
+
+    fn call(&mut self, kind: i32, a: &[u8], b: &[u8]) -> Result<(), String> {
+        self.store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let (a_ptr, a_len) = self.write(a)?;
+        let (b_ptr, b_len) = self.write(b)?;
+        self.on_event
+            .call(&mut self.store, (kind, a_ptr, a_len, b_ptr, b_len))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Copies `bytes` to memory the module allocated.
+    fn write(&mut self, bytes: &[u8]) -> Result<(i32, i32), String> {
+        if bytes.is_empty() {
+            return Ok((0, 0));
+        }
+        let len = i32::try_from(bytes.len()).map_err(|e| e.to_string())?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|e| e.to_string())?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| e.to_string())?;
+        Ok((ptr, len))
+    }
+}
+
+impl Plugin for WasmPlugin {
+    fn manifest(&self) -> Manifest {
+        self.manifest.clone()
+    }
+
+    fn on(&mut self, cx: &mut Context, event: Lifecycle) {
+        if self.stopped {
+            return;
+        }
+        let (kind, a, b) = encode(event);
+        self.store.data_mut().cx = Some(cx.clone());
+        let result = self.call(kind, &a, &b);
+
+        let state = self.store.data_mut();
+        if let (Some(items), Some(done)) = (state.status.take(), state.cx.as_mut()) {
+            let _ = done.set_status(items);
+        }
+        if let Some(done) = state.cx.take() {
+            *cx = done;
+        }
+        if let Err(e) = result {
+            println!("Plugin {} stopped: {e}", self.manifest.name);
+            self.stopped = true;
+        }
+    }
+}
+
+/// The kind and arguments a lifecycle event is passed with.
+fn encode(event: Lifecycle) -> (i32, Vec<u8>, Vec<u8>) {
+    let path = |path: &Path| path.to_string_lossy().into_owned().into_bytes();
+    match event {
+        Lifecycle::Activated => (0, Vec::new(), Vec::new()),
+        Lifecycle::FileOpened(p) => (1, path(&p), Vec::new()),
+        Lifecycle::FileSaved(p) => (2, path(&p), Vec::new()),
+        Lifecycle::FileClosed(p) => (3, path(&p), Vec::new()),
+        Lifecycle::FocusChanged(p) => {
+            (4, p.as_deref().map(path).unwrap_or_default(), Vec::new())
+        }
+        Lifecycle::BufferRead(p, text) => {
+            (5, path(&p), text.as_bytes().to_vec())
+        }
+        Lifecycle::Command(id) => (6, id.into_bytes(), Vec::new()),
+    }
+}
+
+fn read_str(caller: &Caller<'_, State>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let len = usize::try_from(len).ok().filter(|len| *len <= MAX_STRING)?;
+    let mut bytes = vec![0; len];
+    memory.read(caller, ptr as u32 as usize, &mut bytes).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Runs `f` on the plugin's context, turning its result into a status code.
+fn with_cx(
+    caller: &mut Caller<'_, State>,
+    f: impl FnOnce(&mut Context) -> Result<(), PluginError>,
+) -> i32 {
+    match caller.data_mut().cx.as_mut().map(f) {
+        Some(Ok(())) => OK,
+        Some(Err(_)) => DENIED,
+        None => INVALID,
+    }
+}
+
+/// Copies `text` to memory the module allocated and writes its pointer and
+/// length at `out`, as two little-endian `i32`.
+fn return_str(caller: &mut Caller<'_, State>, text: &str, out: i32) -> Option<()> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let alloc = caller
+        .get_export("zen_alloc")?
+        .into_func()?
+        .typed::<i32, i32>(&*caller)
+        .ok()?;
+    let (ptr, len) = if text.is_empty() {
+        (0, 0)
+    } else {
+        let len = i32::try_from(text.len()).ok()?;
+        let ptr = alloc.call(&mut *caller, len).ok()?;
+        memory.write(&mut *caller, ptr as u32 as usize, text.as_bytes()).ok()?;
+        (ptr, len)
+    };
+    let mut pair = [0; 8];
+    pair[..4].copy_from_slice(&ptr.to_le_bytes());
+    pair[4..].copy_from_slice(&len.to_le_bytes());
+    memory.write(&mut *caller, out as u32 as usize, &pair).ok()
+}
+
+/// Passes the text `read` gets from the context back to the module.
+fn read_into(
+    caller: &mut Caller<'_, State>,
+    out: i32,
+    read: impl FnOnce(&Context) -> Result<Option<String>, PluginError>,
+) -> i32 {
+    let text = match caller.data().cx.as_ref().map(read) {
+        Some(Ok(Some(text))) => text,
+        Some(Ok(None)) => String::new(),
+        Some(Err(_)) => return DENIED,
+        None => return INVALID,
+    };
+    match return_str(caller, &text, out) {
+        Some(()) => OK,
+        None => INVALID,
+    }
+}
+
+/// Starts a new list of status items if none was started in this call.
+fn status<'a>(caller: &'a mut Caller<'_, State>) -> Option<&'a mut Vec<StatusItem>> {
+    let state = caller.data_mut();
+    if !state.cx.as_ref()?.has(Capability::StatusItems) {
+        return None;
+    }
+    Some(state.status.get_or_insert_with(Vec::new))
+}
+
+fn link(linker: &mut Linker<State>) -> Result<(), String> {
+    linker
+        .func_wrap("zen", "log", |caller: Caller<'_, State>, ptr: i32, len: i32| {
+            match read_str(&caller, ptr, len) {
+                Some(text) => {
+                    println!("[{}] {text}", caller.data().name);
+                    OK
+                }
+                None => INVALID,
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "read_buffer",
+            |mut caller: Caller<'_, State>, ptr: i32, len: i32| {
+                let Some(path) = read_str(&caller, ptr, len) else {
+                    return INVALID;
+                };
+                with_cx(&mut caller, |cx| cx.read_buffer(path))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "insert_text",
+            |mut caller: Caller<'_, State>, path_ptr: i32, path_len: i32, ptr: i32, len: i32| {
+                let (Some(path), Some(text)) = (
+                    read_str(&caller, path_ptr, path_len),
+                    read_str(&caller, ptr, len),
+                ) else {
+                    return INVALID;
+                };
+                with_cx(&mut caller, |cx| cx.insert_text(path, text))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "replace_buffer",
+            |mut caller: Caller<'_, State>, path_ptr: i32, path_len: i32, ptr: i32, len: i32| {
+                let (Some(path), Some(text)) = (
+                    read_str(&caller, path_ptr, path_len),
+                    read_str(&caller, ptr, len),
+                ) else {
+                    return INVALID;
+                };
+                with_cx(&mut caller, |cx| cx.replace_buffer(path, text))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "register_command",
+            |mut caller: Caller<'_, State>,
+             menu: i32,
+             label_ptr: i32,
+             label_len: i32,
+             id_ptr: i32,
+             id_len: i32| {
+                let menu = usize::try_from(menu)
+                    .ok()
+                    .and_then(|menu| MenuKind::ALL.get(menu).copied());
+                let (Some(menu), Some(label), Some(id)) = (
+                    menu,
+                    read_str(&caller, label_ptr, label_len),
+                    read_str(&caller, id_ptr, id_len),
+                ) else {
+                    return INVALID;
+                };
+                with_cx(&mut caller, |cx| cx.register_command(menu, label, id))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "bind_key",
+            |mut caller: Caller<'_, State>, key: i32, modifiers: i32, id_ptr: i32, id_len: i32| {
+                let (Some(key), Some(id)) = (
+                    char::from_u32(key as u32),
+                    read_str(&caller, id_ptr, id_len),
+                ) else {
+                    return INVALID;
+                };
+                let mut held = Modifiers::empty();
+                held.set(Modifiers::CTRL, modifiers & 1 != 0);
+                held.set(Modifiers::SHIFT, modifiers & 2 != 0);
+                held.set(Modifiers::ALT, modifiers & 4 != 0);
+                with_cx(&mut caller, |cx| cx.bind_key(key, held, id))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "clipboard_entry",
+            |mut caller: Caller<'_, State>, index: i32, out: i32| {
+                read_into(&mut caller, out, |cx| {
+                    let history = cx.clipboard_history()?;
+                    Ok(usize::try_from(index)
+                        .ok()
+                        .and_then(|index| history.get(index).cloned()))
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "push_clipboard",
+            |mut caller: Caller<'_, State>, ptr: i32, len: i32| {
+                let Some(text) = read_str(&caller, ptr, len) else {
+                    return INVALID;
+                };
+                with_cx(&mut caller, |cx| cx.push_clipboard(text))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "read_register",
+            |mut caller: Caller<'_, State>, name: i32, out: i32| {
+                let Some(name) = char::from_u32(name as u32) else {
+                    return INVALID;
+                };
+                read_into(&mut caller, out, |cx| cx.read_register(name))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "write_register",
+            |mut caller: Caller<'_, State>, name: i32, ptr: i32, len: i32| {
+                let (Some(name), Some(text)) = (
+                    char::from_u32(name as u32),
+                    read_str(&caller, ptr, len),
+                ) else {
+                    return INVALID;
+                };
+                match caller.data_mut().cx.as_mut().map(|cx| cx.write_register(name, text)) {
+                    Some(Ok(true)) => OK,
+                    Some(Err(_)) => DENIED,
+                    Some(Ok(false)) | None => INVALID,
+                }
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap("zen", "clear_status", |mut caller: Caller<'_, State>| {
+            match status(&mut caller) {
+                Some(items) => {
+                    items.clear();
+                    OK
+                }
+                None => DENIED,
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    linker
+        .func_wrap(
+            "zen",
+            "add_status",
+            |mut caller: Caller<'_, State>,
+             right: i32,
+             text_ptr: i32,
+             text_len: i32,
+             id_ptr: i32,
+             id_len: i32| {
+                let (Some(text), Some(id)) = (
+                    read_str(&caller, text_ptr, text_len),
+                    read_str(&caller, id_ptr, id_len),
+                ) else {
+                    return INVALID;
+                };
+                let mut item = if right != 0 {
+                    StatusItem::right(text)
+                } else {
+                    StatusItem::left(text)
+                };
+                if !id.is_empty() {
+                    let command = caller.data().cx.as_ref().map(|cx| cx.command(id));
+                    item.command = command.map(Box::new);
+                }
+                match status(&mut caller) {
+                    Some(items) => {
+                        items.push(item);
+                        OK
+                    }
+                    None => DENIED,
+                }
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin whose `zen_on_event` runs `body`.
+    fn plugin(memory_pages: u32, body: &str) -> Result<WasmPlugin, String> {
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") {memory_pages})
+                (func (export "zen_alloc") (param i32) (result i32)
+                    i32.const 0)
+                (func (export "zen_on_event")
+                    (param i32 i32 i32 i32 i32)
+                    {body}))"#
+        );
+        let bytes = wat::parse_str(wat).map_err(|e| e.to_string())?;
+        WasmPlugin::from_bytes(Manifest::new("test", "0.1.0"), &bytes)
+    }
+
+    fn activate(plugin: &mut WasmPlugin) {
+        plugin.on(&mut Context::new("test", Vec::new()), Lifecycle::Activated);
+    }
+
+    #[test]
+    fn keeps_running_a_module_that_returns() {
+        let mut plugin = plugin(1, "").unwrap();
+        activate(&mut plugin);
+        assert!(!plugin.stopped);
+    }
+
+    #[test]
+    fn stops_a_module_that_traps() {
+        let mut plugin = plugin(1, "unreachable").unwrap();
+        activate(&mut plugin);
+        assert!(plugin.stopped);
+    }
+
+    #[test]
+    fn stops_a_module_that_runs_out_of_fuel() {
+        let mut plugin = plugin(1, "(loop (br 0))").unwrap();
+        activate(&mut plugin);
+        assert!(plugin.stopped);
+    }
+
+    #[test]
+    fn refuses_a_module_starting_above_the_memory_limit() {
+        let pages = (MEMORY_LIMIT / 65536 + 1) as u32;
+        assert!(plugin(pages, "").is_err());
+    }
+
+    #[test]
+    fn refuses_to_grow_memory_above_the_limit() {
+        let pages = MEMORY_LIMIT / 65536;
+        // Traps when the memory didn't grow
+        let body = format!(
+            "(if (i32.eq (memory.grow (i32.const {pages})) (i32.const -1))
+                (then unreachable))"
+        );
+        let mut plugin = plugin(1, &body).unwrap();
+        activate(&mut plugin);
+        assert!(plugin.stopped);
+    }
+}
@@ -0,0 +1,188 @@
+//! The sidebars and bottom panel around the central area, see
+//! [`Register::dock`](registers::Register::dock). Their sizes and whether
+//! they are collapsed are kept between runs.
+
+use std::path::PathBuf;
+
+use iced::{
+    mouse,
+    widget::{horizontal_rule, mouse_area, vertical_rule},
+    Element, Point, Size,
+};
+use registers::{Dock, Event};
+use serde::{Deserialize, Serialize};
+
+/// The smallest a dock is dragged to, and what it leaves to the central
+/// area at least.
+const MIN_SIZE: f32 = 80.0;
+/// Thickness of the edge dragged to resize a dock.
+const HANDLE: f32 = 6.0;
+
+/// A sidebar or the bottom panel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Panel {
+    /// The width of a sidebar or the height of the bottom panel.
+    pub size: f32,
+    pub collapsed: bool,
+}
+
+impl Panel {
+    fn sized(size: f32) -> Self {
+        Self {
+            size,
+            collapsed: false,
+        }
+    }
+}
+
+/// The sidebars and bottom panel, as stored in `docks.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Docks {
+    pub left: Panel,
+    pub right: Panel,
+    pub bottom: Panel,
+    /// The dock whose edge is being dragged.
+    #[serde(skip)]
+    pub dragging: Option<Dock>,
+}
+
+impl Default for Docks {
+    fn default() -> Self {
+        Self {
+            left: Panel::sized(250.0),
+            right: Panel::sized(350.0),
+            bottom: Panel::sized(200.0),
+            dragging: None,
+        }
+    }
+}
+
+impl Docks {
+    pub fn path() -> Option<PathBuf> {
+        Some(registers::paths::data_dir()?.join("docks.json"))
+    }
+
+    pub fn load() -> Self {
+        let Some(content) =
+            Self::path().and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            println!("Ignoring invalid dock layout: {e}");
+            Self::default()
+        })
+    }
+
+    pub fn store(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(self)
+                    .map_err(std::io::Error::other)?;
+                std::fs::write(&path, json)
+            });
+        if let Err(e) = written {
+            println!("Unable to write dock layout {:?}: {e}", path);
+        }
+    }
+
+    /// The panel of `dock`, `None` for the docks that can't be resized.
+    pub fn panel(&self, dock: Dock) -> Option<Panel> {
+        match dock {
+            Dock::Left => Some(self.left),
+            Dock::Right => Some(self.right),
+            Dock::Bottom => Some(self.bottom),
+            _ => None,
+        }
+    }
+
+    fn panel_mut(&mut self, dock: Dock) -> Option<&mut Panel> {
+        match dock {
+            Dock::Left => Some(&mut self.left),
+            Dock::Right => Some(&mut self.right),
+            Dock::Bottom => Some(&mut self.bottom),
+            _ => None,
+        }
+    }
+
+    pub fn toggle(&mut self, dock: Dock) {
+        if let Some(panel) = self.panel_mut(dock) {
+            panel.collapsed = !panel.collapsed;
+        }
+    }
+
+    pub fn expand(&mut self, dock: Dock) {
+        if let Some(panel) = self.panel_mut(dock) {
+            panel.collapsed = false;
+        }
+    }
+
+    pub fn resize(&mut self, dock: Dock, size: f32) {
+        if let Some(panel) = self.panel_mut(dock) {
+            panel.size = size.max(MIN_SIZE);
+        }
+    }
+
+    /// The size `dock` is shown at in the `area` holding the docks and the
+    /// central area, with the `shown` docks. Smaller than stored when the
+    /// window shrank, so the central area keeps `MIN_SIZE`; the sidebars
+    /// share what is left.
+    pub fn fitted(&self, dock: Dock, shown: &[Dock], area: Size) -> f32 {
+        let size = |dock: Dock| {
+            self.panel(dock)
+                .filter(|_| shown.contains(&dock))
+                .map_or(0.0, |panel| panel.size)
+        };
+        match dock {
+            Dock::Bottom => size(dock).min((area.height - MIN_SIZE).max(0.0)),
+            Dock::Left | Dock::Right => {
+                let sides = size(Dock::Left) + size(Dock::Right);
+                let room = (area.width - MIN_SIZE).max(0.0);
+                if sides <= room {
+                    size(dock)
+                } else {
+                    size(dock) * room / sides
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// The size `dock` gets when its edge is dragged to `cursor`, in the `area`
+/// holding the docks and the central area.
+pub fn dragged_size(dock: Dock, cursor: Point, area: Size) -> f32 {
+    let (size, room) = match dock {
+        Dock::Left => (cursor.x, area.width),
+        Dock::Right => (area.width - cursor.x, area.width),
+        Dock::Bottom => (area.height - cursor.y, area.height),
+        _ => return MIN_SIZE,
+    };
+    size.clamp(MIN_SIZE, (room - MIN_SIZE).max(MIN_SIZE))
+}
+
+/// The edge of `dock`, dragged to resize it. At least as thick as the
+/// spacing between registers, which it replaces.
+pub fn handle<'a>(dock: Dock, spacing: f32) -> Element<'a, Event> {
+    let thickness = spacing.max(HANDLE);
+    let (edge, interaction): (Element<'a, Event>, _) = match dock {
+        Dock::Bottom => (
+            horizontal_rule(thickness).into(),
+            mouse::Interaction::ResizingVertically,
+        ),
+        _ => (
+            vertical_rule(thickness).into(),
+            mouse::Interaction::ResizingHorizontally,
+        ),
+    };
+    mouse_area(edge)
+        .on_press(Event::DockDrag(Some(dock)))
+        .interaction(interaction)
+        .into()
+}
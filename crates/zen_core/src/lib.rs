@@ -1,14 +1,41 @@
-use iced::{widget::{container, Column}, Element, Length, Subscription, Task, Theme};
-pub use registers::{Register, Event};
+use std::sync::Arc;
+
+use iced::{
+    mouse,
+    widget::{column, container, mouse_area, responsive, Column, Row, Space},
+    window, Element, Length, Size, Subscription, Task, Theme,
+};
+pub use registers::{
+    commands, paths, Command, Dock, Event, Marker, MarkerKind, MenuKind, Register,
+    Requires, Target
+};
 
 // registers
-pub use editor::{Editor, key_bindings::*};
+pub use editor::{
+    Editor, autosave::AutoSave, clipboard_panel::ClipboardPanel, key_bindings::*,
+    file_browser::{FileBrowser, InAppPicker}, 
+    filesystem::{FileSystem, LocalFs, MemoryFs}, quick_open::QuickOpen,
+    recovery::Recovery, session::Session
+};
 pub use top_menu::TopMenu;
+pub use status_bar::StatusBar;
+pub use settings::{self, SettingsEditor};
+pub use plugins::{self, Capability, Plugin, PluginError, PluginHost};
+pub use wasm_plugins::{self, WasmPlugin};
+pub use scripting;
+
+pub mod recording;
+pub use recording::{Recorder, Recording};
+
+pub mod docks;
+pub use docks::Docks;
 
 pub struct ZenCore {
     pub title: String,
     pub theme: Theme,
     registers: Vec<Box<dyn registers::Register>>,
+    recorder: Option<Recorder>,
+    docks: Docks,
 }
 
 impl ZenCore {
@@ -17,6 +44,8 @@ impl ZenCore {
             title,
             theme,
             registers: Vec::new(),
+            recorder: None,
+            docks: Docks::load(),
         }
     }
     
@@ -29,6 +58,8 @@ impl ZenCore {
             title,
             theme,
             registers,
+            recorder: None,
+            docks: Docks::load(),
         }
     }
         
@@ -41,54 +72,280 @@ impl ZenCore {
         self.register(Box::new(register))
     }
     
+    /// Loads a plugin in a register of its own, after the registers already
+    /// there, with the capabilities it asks for that are `granted`. The
+    /// task runs what the plugin did once activated.
+    pub fn load_plugin(
+        &mut self, 
+        plugin: Box<dyn Plugin>, 
+        granted: &[Capability],
+    ) -> Result<Task<Event>, PluginError> {
+        let name = plugin.manifest().name;
+        if self.registers.iter().any(|r| r.name() == name) {
+            return Err(PluginError::NameTaken(name));
+        }
+        let (host, task) = PluginHost::load(plugin, granted)?;
+        self.register_with(host);
+        Ok(task)
+    }
+    
+    /// The first register of type `R`.
+    pub fn get<R: Register + 'static>(&self) -> Option<&R> {
+        self.registers.iter().find_map(|r| r.as_ref().as_any().downcast_ref())
+    }
+    
+    pub fn get_mut<R: Register + 'static>(&mut self) -> Option<&mut R> {
+        self.registers.iter_mut()
+            .find_map(|r| r.as_mut().as_any_mut().downcast_mut())
+    }
+    
+    /// Writes every event from now on to the recording.
+    pub fn record(&mut self, recorder: Recorder) -> &mut Self {
+        self.recorder = Some(recorder);
+        self
+    }
+    
+    /// Feeds the events of a recording to the registers, without running
+    /// the tasks they return as their results were recorded too. Returns
+    /// how many were replayed.
+    ///
+    /// The registers should read the buffers of the recording instead of
+    /// the disk, and write to a folder [`paths::redirect`] points to.
+    pub fn replay(&mut self, recording: &Recording) -> usize {
+        let mut replayed = 0;
+        for recorded in &recording.events {
+            if !recording::replayed(&recorded.event) {
+                continue;
+            }
+            let _ = self.update(recorded.event.clone());
+            replayed += 1;
+        }
+        replayed
+    }
+    
+    pub fn docks(&self) -> &Docks {
+        &self.docks
+    }
+    
+    /// The sidebars and bottom panel with an open register.
+    fn open_docks(&self) -> Vec<Dock> {
+        Dock::PANELS.into_iter()
+            .filter(|dock| {
+                self.registers.iter().any(|r| r.dock() == *dock && r.is_open())
+            })
+            .collect()
+    }
+    
+    /// The registers of `dock`, one above the other.
+    fn stacked(&self, dock: Dock, spacing: f32) -> Column<'_, Event> {
+        self.registers.iter()
+            .filter(|r| r.dock() == dock)
+            .fold(
+                Column::new().spacing(spacing).width(Length::Fill),
+                |c, r| c.push(r.view())
+            )
+    }
     
+    /// The edge and the registers of a sidebar or the bottom panel, fitted
+    /// in `area`, as empty space when it isn't one of the `shown` docks.
+    fn panel(
+        &self, 
+        dock: Dock, 
+        shown: &[Dock], 
+        area: Size, 
+        spacing: f32,
+    ) -> [Element<'_, Event>; 2] {
+        if !shown.contains(&dock) {
+            let empty = || Element::from(Space::new(Length::Shrink, Length::Shrink));
+            return [empty(), empty()];
+        }
+        let size = self.docks.fitted(dock, shown, area);
+        let (width, height) = match dock {
+            Dock::Bottom => (Length::Fill, Length::Fixed(size)),
+            _ => (Length::Fixed(size), Length::Fill),
+        };
+        let stacked = container(self.stacked(dock, spacing))
+            .width(width)
+            .height(height);
+        [docks::handle(dock, spacing), stacked.into()]
+    }
+    
+    /// The sidebars, the central area and the bottom panel, filling `area`.
+    /// Hidden docks keep their place as empty space, so the central area
+    /// keeps the state of its widgets when they are toggled.
+    fn docked(&self, area: Size, spacing: f32) -> Element<'_, Event> {
+        // The docks that are expanded and hold an open register
+        let shown: Vec<Dock> = self.open_docks().into_iter()
+            .filter(|dock| {
+                self.docks.panel(*dock).is_some_and(|panel| !panel.collapsed)
+            })
+            .collect();
+        
+        let [bottom_edge, bottom] = 
+            self.panel(Dock::Bottom, &shown, area, spacing);
+        let center = column![
+            self.stacked(Dock::Center, spacing).height(Length::Fill),
+            bottom_edge,
+            bottom,
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill);
+        
+        let [left_edge, left] = self.panel(Dock::Left, &shown, area, spacing);
+        let [right_edge, right] = self.panel(Dock::Right, &shown, area, spacing);
+        let row = Row::new()
+            .push(left)
+            .push(left_edge)
+            .push(center)
+            .push(right_edge)
+            .push(right)
+            .height(Length::Fill);
+        
+        let docked = mouse_area(row);
+        match self.docks.dragging {
+            Some(dock) => docked
+                .on_move(move |cursor| {
+                    Event::ResizeDock(dock, docks::dragged_size(dock, cursor, area))
+                })
+                .on_release(Event::DockDrag(None))
+                .into(),
+            None => docked.into(),
+        }
+    }
+}
+
+/// The registers of Zen around `editor`, in the order they are updated:
+/// the panels before the editor, the status bar after it. Files are read
+/// from and written to `fs`.
+pub fn default_registers(
+    editor: Editor, 
+    fs: Arc<dyn FileSystem>,
+) -> Vec<Box<dyn Register>> {
+    vec![
+        Box::new(TopMenu::new()),
+        Box::new(Recovery::new(fs.clone())),
+        Box::new(SettingsEditor::new()),
+        Box::new(QuickOpen::new(fs.clone())),
+        Box::new(ClipboardPanel::new()),
+        Box::new(FileBrowser::new(fs)),
+        Box::new(editor),
+        Box::new(StatusBar::new()),
+    ]
 }
 
 impl Register for ZenCore {
     fn update(&mut self, event: Event) -> Task<Event> {
-        match event {
+        if let Some(recorder) = &mut self.recorder {
+            if !matches!(event, Event::None) {
+                recorder.record(&event);
+            }
+        }
+        let opened = self.open_docks();
+        let task = match event {
             Event::None => Task::none(),
-            Event::ThemeChanged(th) => {
-                if th.is_dark(){
-                    self.theme = Theme::Dark;
-                }else{
-                    self.theme = Theme::Light;
+            Event::UiThemeChanged(ref theme) => {
+                self.theme = theme.clone();
+                Task::batch(
+                    self.registers.iter_mut().map(|r| r.update(event.clone()))
+                )
+            },
+            Event::SettingsChanged | Event::ThemesReloaded => {
+                let settings = settings::current();
+                self.title = settings.ui.title.clone();
+                if let Some(theme) = settings.ui_theme() {
+                    self.theme = theme;
                 }
-                self.registers.iter_mut().fold(
-                    Task::none(),
-                    |t, r| 
-                    t.chain(r.update(event.clone()))
+                Task::batch(
+                    self.registers.iter_mut().map(|r| r.update(event.clone()))
                 )
             },
+            Event::To(target, event) => {
+                Task::batch(
+                    self.registers.iter_mut()
+                        .filter(|r| target.reaches(&r.name()))
+                        .map(|r| r.route(
+                            target.clone().within_register(), 
+                            (*event).clone()
+                        ))
+                )
+            },
+            Event::CloseRequested(id) => {
+                Task::batch(
+                    self.registers.iter_mut().map(|r| r.update(event.clone()))
+                ).chain(window::close(id))
+            },
+            Event::ToggleDock(dock) => {
+                self.docks.toggle(dock);
+                self.docks.store();
+                Task::none()
+            },
+            Event::DockDrag(dock) => {
+                let released = dock.is_none() && self.docks.dragging.is_some();
+                self.docks.dragging = dock;
+                if released {
+                    self.docks.store();
+                }
+                Task::none()
+            },
+            Event::ResizeDock(dock, size) => {
+                self.docks.resize(dock, size);
+                // While dragging, stored once released
+                if self.docks.dragging.is_none() {
+                    self.docks.store();
+                }
+                Task::none()
+            },
             _ => {
-                self.registers.iter_mut().fold(
-                    Task::none(),
-                    |t, r| 
-                    t.chain(r.update(event.clone()))
+                Task::batch(
+                    self.registers.iter_mut().map(|r| r.update(event.clone()))
                 )
             }
+        };
+        // A collapsed dock shows again when one of its registers opens
+        for dock in self.open_docks() {
+            if !opened.contains(&dock) {
+                self.docks.expand(dock);
+            }
         }
+        task
     }
     
     fn subscription(&self) -> Subscription<Event> {
+        // A drag ends wherever the button is released, or when the cursor
+        // leaves the window, not only over the docked area
+        let drag = if self.docks.dragging.is_some() {
+            iced::event::listen_with(|event, _, _| match event {
+                iced::Event::Mouse(
+                    mouse::Event::ButtonReleased(mouse::Button::Left)
+                    | mouse::Event::CursorLeft
+                ) => Some(Event::DockDrag(None)),
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
         Subscription::batch(
             self.registers.iter()
                 .map(|r| r.subscription())
+                .chain([
+                    window::close_requests().map(Event::CloseRequested),
+                    drag,
+                ])
         )
     }
     
     fn view(&self) -> Element<'_, Event> {
-       let mut view = Column::new()
-           .spacing(5)
-           .padding(5)
-           .width(Length::Fill)
-           .height(Length::Fill);
-       
-       view = self.registers.iter().fold(
-           view, 
-           |v,r| 
-               v.push(r.view())
-       );
+       let settings = settings::current();
+       let spacing = settings.ui.spacing;
+       let view = column![
+           self.stacked(Dock::Top, spacing),
+           responsive(move |area| self.docked(area, spacing)),
+           self.stacked(Dock::Footer, spacing),
+       ]
+       .spacing(spacing)
+       .padding(settings.ui.padding)
+       .width(Length::Fill)
+       .height(Length::Fill);
        
        container(view)
            .center(Length::Fill)
@@ -0,0 +1,163 @@
+//! Recording the events of a session to a file and reading them back, so a
+//! bug can be reproduced by replaying them, see [`ZenCore::replay`].
+//!
+//! A recording holds one JSON document per line: a [`Header`] with the
+//! settings, session and buffers it started from, then every event with
+//! the milliseconds since it started. The results of tasks, like the
+//! content of opened files, come back as events and are recorded too, so a
+//! replay doesn't need the files of whoever recorded it. Recordings hold
+//! those contents, mind it before sharing one.
+//!
+//! [`ZenCore::replay`]: crate::ZenCore::replay
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, LineWriter, Write},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use editor::session::Session;
+use registers::Event;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+
+/// Bumped when older recordings can't be read anymore.
+const FORMAT: u32 = 2;
+
+/// What a recording starts from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub format: u32,
+    /// The version of Zen it was recorded with.
+    pub version: String,
+    /// Seconds since the Unix epoch.
+    pub started: u64,
+    pub settings: Settings,
+    pub session: Option<Session>,
+    /// The text of the files the session opened, as they were read.
+    pub buffers: BTreeMap<PathBuf, String>,
+}
+
+/// An event and when it came, in milliseconds since the recording started.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Recorded {
+    pub at: u64,
+    pub event: Event,
+}
+
+/// A [`Recorded`] event, written without cloning it.
+#[derive(Serialize)]
+struct Line<'a> {
+    at: u64,
+    event: &'a Event,
+}
+
+/// Writes every event to the recording as it comes, so it survives a crash.
+pub struct Recorder {
+    file: LineWriter<File>,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Starts a recording at `path`, from the current settings and the
+    /// session Zen was started with, holding `buffers`.
+    pub fn create(
+        path: &Path,
+        session: Option<Session>,
+        buffers: BTreeMap<PathBuf, String>,
+    ) -> io::Result<Self> {
+        let header = Header {
+            format: FORMAT,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            settings: (*settings::current()).clone(),
+            session,
+            buffers,
+        };
+        let mut file = LineWriter::new(File::create(path)?);
+        let header = serde_json::to_string(&header).map_err(io::Error::other)?;
+        writeln!(file, "{header}")?;
+        Ok(Self {
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, event: &Event) {
+        let line = Line {
+            at: self.started.elapsed().as_millis() as u64,
+            event,
+        };
+        let written = serde_json::to_string(&line)
+            .map_err(io::Error::other)
+            .and_then(|line| writeln!(self.file, "{line}"));
+        if let Err(e) = written {
+            println!("Unable to record {event:?}: {e}");
+        }
+    }
+}
+
+/// A recording read back.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: Header,
+    pub events: Vec<Recorded>,
+}
+
+impl Recording {
+    /// Reads the recording at `path`. A last line cut short by a crash is
+    /// left out.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        let (first, rest) = lines.split_first().ok_or("The recording is empty")?;
+
+        let header: Header = serde_json::from_str(first)
+            .map_err(|e| format!("Invalid header: {e}"))?;
+        if header.format != FORMAT {
+            return Err(format!(
+                "Recorded in format {}, this version reads {FORMAT}",
+                header.format
+            ));
+        }
+
+        let mut events = Vec::with_capacity(rest.len());
+        for (i, line) in rest.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(recorded) => events.push(recorded),
+                Err(_) if i + 1 == rest.len() => break,
+                Err(e) => return Err(format!("Invalid event on line {}: {e}", i + 2)),
+            }
+        }
+        Ok(Self { header, events })
+    }
+}
+
+/// Whether `event` is fed back on replay. Changes, scans and reloads read the
+/// files of whoever replays, what they found was recorded as the events
+/// that followed. Applying a setting, laying out the docks and closing the
+/// window would write their settings, dock layout and session.
+pub fn replayed(event: &Event) -> bool {
+    !matches!(
+        event,
+        Event::SettingsFileChanged
+            | Event::ThemesDirChanged
+            | Event::ScanAllFiles
+            | Event::ScanFile(_)
+            | Event::OpenPath(_)
+            | Event::AutoSaveTick
+            | Event::WriteSwaps
+            | Event::SettingsApply(..)
+            | Event::ToggleDock(_)
+            | Event::DockDrag(_)
+            | Event::ResizeDock(..)
+            | Event::CloseRequested(_)
+    )
+}
@@ -1,32 +1,244 @@
+use std::{path::{Path, PathBuf}, sync::Arc};
+
 use iced::Task;
-use zen_core::{Editor, KeyBinding, Event, Register, ZenCore};
+use zen_core::{
+    plugins::Manifest, settings::Settings, Capability, commands, Command, Dock, Editor,
+    FileSystem, InAppPicker, KeyBinding, Event, LocalFs, MemoryFs, MenuKind, Plugin,
+    Recorder, Recording, Register, Requires, Session, ZenCore
+};
 
 fn main() {
-    let _ = iced::application("Zen editor", ZenCore::update, ZenCore::view)
+    let mut args = std::env::args().skip(1);
+    match (args.next().as_deref(), args.next()) {
+        (Some("--replay"), Some(path)) => replay(Path::new(&path)),
+        (Some("--record"), Some(path)) => run(Some(PathBuf::from(path))),
+        _ => run(None),
+    }
+}
+
+/// Runs Zen, writing its events to the recording at `recording` if any.
+fn run(recording: Option<PathBuf>) {
+    let _ = iced::application(ZenCore::title, ZenCore::update, ZenCore::view)
         .subscription(ZenCore::subscription)
         .theme(ZenCore::theme)
         .font(include_bytes!("../fonts/fontello/fontello.ttf"))
         .font(iced_aw::iced_fonts::REQUIRED_FONT_BYTES)
+        .exit_on_close_request(false)
         .run_with(
-            || -> (ZenCore, Task<Event>) { 
-                let mut editor = Editor::new();
-                editor.add_keys_bindings(vec![
-                    KeyBinding::new('s', true, false, false, Event::Save),
-                    KeyBinding::new('q', true, false, false, Event::Quit(None)),
-                    KeyBinding::new('o', true, false, false, Event::OpenFile),
-                    KeyBinding::new('r', true, false, false, Event::ScanAllFiles),
-                    KeyBinding::new('r', true, true,  false, Event::ScanFile(None)),
-                ]);
-                
-                let top_menu = Box::new(zen_core::TopMenu::new());
-                
-                (
-                    ZenCore::new_with(
-                        "Zen Editor (v1)".to_string(), 
-                        iced::Theme::Dark, 
-                        vec![top_menu, Box::new(editor)]
-                    ),
-                    Task::none()
-                )
+            move || -> (ZenCore, Task<Event>) { 
+                let fs: Arc<dyn FileSystem> = Arc::new(LocalFs);
+                let session = Session::load(fs.as_ref());
+                let (mut core, task) = zen(session.clone(), fs);
+                if let Some(path) = &recording {
+                    let buffers = core.get::<Editor>()
+                        .map(Editor::buffers)
+                        .unwrap_or_default();
+                    match Recorder::create(path, session, buffers) {
+                        Ok(recorder) => {
+                            core.record(recorder);
+                        }
+                        Err(e) => println!("Unable to record to {:?}: {e}", path),
+                    }
+                }
+                (core, task)
             });
 }
+
+/// Replays a recording without a window, to reproduce a bug.
+fn replay(path: &Path) {
+    let recording = match Recording::read(path) {
+        Ok(recording) => recording,
+        Err(e) => {
+            println!("Unable to read recording {:?}: {e}", path);
+            return;
+        }
+    };
+    // Away from the files of whoever replays: the buffers are the recorded
+    // ones, and the settings, plugins and scripts of the scratch folder
+    // are none, so only what was recorded happens
+    let scratch = std::env::temp_dir()
+        .join(format!("zen-replay-{}", std::process::id()));
+    zen_core::paths::redirect(&scratch);
+    zen_core::settings::store::replace(recording.header.settings.clone());
+    let fs = recording.header.buffers.iter()
+        .fold(MemoryFs::new(), |fs, (path, text)| fs.with(path, text.clone()));
+    let (mut core, _) = zen(recording.header.session.clone(), Arc::new(fs));
+    let replayed = core.replay(&recording);
+    println!("Replayed {replayed} of {} events", recording.events.len());
+    let _ = std::fs::remove_dir_all(&scratch);
+}
+
+/// Zen with every register, started from `session` and reading and
+/// writing files in `fs`.
+fn zen(session: Option<Session>, fs: Arc<dyn FileSystem>) -> (ZenCore, Task<Event>) {
+    let mut editor = session.as_ref()
+        .map_or_else(Editor::new, |session| Editor::from_session(session, fs.clone()));
+    editor.set_filesystem(fs.clone());
+    let restore = session.as_ref()
+        .and_then(Session::theme)
+        .map_or_else(Task::none, |theme| {
+            Task::done(Event::ThemeChanged(theme))
+        });
+    let settings = zen_core::settings::current();
+    let font = zen_core::settings::fonts::load_file(
+        &settings.editor.font_file
+    );
+    editor.add_keys_bindings(vec![
+        KeyBinding::new('s', true, false, false, Event::Save),
+        KeyBinding::new('q', true, false, false, Event::Quit(None)),
+        KeyBinding::new('o', true, false, false, Event::OpenFile),
+        KeyBinding::new('o', true, true,  false, Event::OpenFolder),
+        KeyBinding::new('p', true, false, false, Event::ToggleQuickOpen),
+        KeyBinding::new('n', true, false, false, Event::NewFile),
+        KeyBinding::new('r', true, false, false, Event::ScanAllFiles),
+        KeyBinding::new('r', true, true,  false, Event::ScanFile(None)),
+        KeyBinding::new('z', true, false, false, Event::Undo),
+        KeyBinding::new('z', true, true,  false, Event::Redo),
+        KeyBinding::new('y', true, false, false, Event::Redo),
+        KeyBinding::new('d', true, false, false, Event::AddNextOccurrence),
+        KeyBinding::new('l', true, true,  false, Event::SelectAllOccurrences),
+        KeyBinding::new('m', true, true,  false, Event::TogglePreview(None)),
+        KeyBinding::new(',', true, false, false, Event::ToggleSettings),
+        KeyBinding::new('=', true, false, false, Event::ZoomIn),
+        KeyBinding::new('+', true, true,  false, Event::ZoomIn),
+        KeyBinding::new('-', true, false, false, Event::ZoomOut),
+        KeyBinding::new('0', true, false, false, Event::ZoomReset),
+        KeyBinding::new('=', true, false, true,  Event::GlobalZoomIn),
+        KeyBinding::new('+', true, true,  true,  Event::GlobalZoomIn),
+        KeyBinding::new('-', true, false, true,  Event::GlobalZoomOut),
+        KeyBinding::new('0', true, false, true,  Event::GlobalZoomReset),
+        KeyBinding::new('r', true, false, true,  Event::RecordMacro(None)),
+        KeyBinding::new('p', true, false, true,  Event::PlayMacro(None, Some(1))),
+        KeyBinding::new('e', true, false, true,  Event::PlayMacro(None, None)),
+        KeyBinding::new('v', true, true,  false, Event::ToggleClipboard),
+        KeyBinding::new('b', true, false, false, Event::ToggleDock(Dock::Left)),
+        KeyBinding::new('b', true, false, true,  Event::ToggleDock(Dock::Right)),
+        KeyBinding::new('j', true, false, false, Event::ToggleDock(Dock::Bottom)),
+    ]);
+    if !settings.ui.native_dialogs {
+        editor.set_picker(Arc::new(InAppPicker));
+    }
+    
+    commands::register_all(default_commands());
+    // After the default commands, so its own come last
+    let script = Task::batch(
+        zen_core::scripting::init().into_iter().map(Task::done)
+    );
+    
+    zen_core::settings::recent::prune(fs.as_ref());
+    let mut core = ZenCore::new_with(
+        settings.ui.title.clone(), 
+        settings.ui_theme().unwrap_or(iced::Theme::Dark), 
+        zen_core::default_registers(editor, fs),
+    );
+    let plugins: Vec<Task<Event>> = zen_core::wasm_plugins::load_all()
+        .into_iter()
+        .filter_map(|plugin| {
+            let granted = granted(&settings, &plugin.manifest());
+            match core.load_plugin(Box::new(plugin), &granted) {
+                Ok(activated) => Some(activated),
+                Err(e) => {
+                    println!("Unable to load plugin: {e}");
+                    None
+                }
+            }
+        })
+        .collect();
+    
+    (core, Task::batch([font, restore, Task::batch(plugins), script]))
+}
+
+/// What the settings grant of what a plugin asks for, telling about the
+/// rest so the user can grant it.
+fn granted(settings: &Settings, manifest: &Manifest) -> Vec<Capability> {
+    let granted: Vec<Capability> = settings.plugins
+        .get(&manifest.name)
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.parse().ok())
+        .collect();
+    for capability in &manifest.capabilities {
+        if !granted.contains(capability) {
+            println!(
+                "Plugin {} asks for {capability}, grant it under [plugins] in the settings",
+                manifest.name
+            );
+        }
+    }
+    granted
+}
+
+fn default_commands() -> Vec<Command> {
+    use MenuKind::*;
+    vec![
+        Command::new(File, "New File", Event::NewFile),
+        Command::new(File, "Open File...", Event::OpenFile),
+        Command::new(File, "Open Folder...", Event::OpenFolder),
+        Command::new(File, "Quick Open...", Event::ToggleQuickOpen),
+        Command::new(File, "Save", Event::Save).requires(Requires::File),
+        Command::new(File, "Reload File", Event::ScanFile(None))
+            .requires(Requires::Text),
+        Command::new(File, "Reload All Files", Event::ScanAllFiles),
+        Command::new(File, "Close File", Event::Quit(None))
+            .requires(Requires::File),
+        Command::new(File, "Settings", Event::ToggleSettings),
+        
+        Command::new(Edit, "Undo", Event::Undo).requires(Requires::File),
+        Command::new(Edit, "Redo", Event::Redo).requires(Requires::File),
+        Command::new(Edit, "Copy", Event::Copy).requires(Requires::Selection),
+        Command::new(Edit, "Cut", Event::Cut).requires(Requires::Selection),
+        Command::new(Edit, "Clipboard History...", Event::ToggleClipboard),
+        Command::new(Edit, "Toggle Line Endings", Event::ToggleLineEndings)
+            .requires(Requires::Text),
+        Command::new(Edit, "Start/Stop Recording Macro", Event::RecordMacro(None)),
+        Command::new(Edit, "Play Last Macro", Event::PlayMacro(None, Some(1)))
+            .requires(Requires::Text),
+        Command::new(
+            Edit, "Play Last Macro to End of File", Event::PlayMacro(None, None)
+        ).requires(Requires::Text),
+        Command::new(Edit, "Bind Key to Last Macro...", Event::BindMacroKey(None)),
+        
+        Command::new(Selection, "Add Cursor Above", Event::AddCursorAbove)
+            .requires(Requires::Text),
+        Command::new(Selection, "Add Cursor Below", Event::AddCursorBelow)
+            .requires(Requires::Text),
+        Command::new(Selection, "Add Next Occurrence", Event::AddNextOccurrence)
+            .requires(Requires::Text),
+        Command::new(
+            Selection, "Select All Occurrences", Event::SelectAllOccurrences
+        ).requires(Requires::Text),
+        Command::new(Selection, "Clear Cursors", Event::ClearCursors)
+            .requires(Requires::Selection),
+        
+        Command::new(View, "Zoom In", Event::ZoomIn),
+        Command::new(View, "Zoom Out", Event::ZoomOut),
+        Command::new(View, "Reset Zoom", Event::ZoomReset),
+        Command::new(View, "Zoom In All Panes", Event::GlobalZoomIn),
+        Command::new(View, "Zoom Out All Panes", Event::GlobalZoomOut),
+        Command::new(View, "Reset Zoom of All Panes", Event::GlobalZoomReset),
+        Command::new(View, "Toggle Left Sidebar", Event::ToggleDock(Dock::Left)),
+        Command::new(View, "Toggle Right Sidebar", Event::ToggleDock(Dock::Right)),
+        Command::new(View, "Toggle Bottom Panel", Event::ToggleDock(Dock::Bottom)),
+        Command::new(View, "Markdown Preview", Event::TogglePreview(None))
+            .requires(Requires::Markdown),
+        Command::new(View, "Image Actual Size", Event::ImageActualSize)
+            .requires(Requires::Image),
+        Command::new(View, "Fit Image", Event::ImageFit)
+            .requires(Requires::Image),
+        
+        Command::new(Go, "Next Tab", Event::NextTab).requires(Requires::File),
+        Command::new(Go, "Previous Tab", Event::PreviousTab)
+            .requires(Requires::File),
+        Command::new(Go, "First Line", Event::RevealLine(0))
+            .requires(Requires::Text),
+        
+        Command::new(
+            Help, "Zen on GitHub", 
+            Event::OpenLink(env!("CARGO_PKG_REPOSITORY").to_string())
+        ),
+        Command::new(
+            Help, "Report an Issue", 
+            Event::OpenLink(format!("{}/issues", env!("CARGO_PKG_REPOSITORY")))
+        ),
+    ]
+}